@@ -7,6 +7,9 @@
 # - wasi-sdk or Emscripten for C: https://github.com/WebAssembly/wasi-sdk
 #
 # Usage: ./build.sh [rust|tinygo|c|all]
+#
+# Set RUST_FEATURES to pass Cargo features to the Rust build, e.g.
+#   RUST_FEATURES=cap-4k ./build.sh rust
 
 SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)"
 cd "$SCRIPT_DIR"
@@ -34,7 +37,7 @@ build_rust() {
     # Enable SIMD and other optimizations via RUSTFLAGS
     export RUSTFLAGS="-C target-feature=+simd128"
 
-    if ! cargo build --release --target wasm32-unknown-unknown; then
+    if ! cargo build --release --target wasm32-unknown-unknown ${RUST_FEATURES:+--features "$RUST_FEATURES"}; then
         echo "ERROR: Rust build failed"
         cd ..
         return 1
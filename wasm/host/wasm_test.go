@@ -5,6 +5,7 @@ import (
 	"math/rand"
 	"os"
 	"path/filepath"
+	"sort"
 	"testing"
 )
 
@@ -75,6 +76,89 @@ func goDot(a, b []float64) float64 {
 	return dot
 }
 
+// goPercentile is numpy's default (type 7) percentile: linear interpolation
+// between the closest ranks of the sorted data.
+func goPercentile(data []float64, p float64) float64 {
+	s := append([]float64(nil), data...)
+	sort.Float64s(s)
+	h := float64(len(s)-1) * p / 100
+	lo := int(math.Floor(h))
+	if lo+1 >= len(s) {
+		return s[lo]
+	}
+	return s[lo] + (h-float64(lo))*(s[lo+1]-s[lo])
+}
+
+// goHistogram counts data into bins equal-width buckets over [lo, hi]. Buckets
+// are half-open except the last; out-of-range values go to the end buckets
+// and NaNs are dropped. hi <= lo puts everything in bucket 0.
+func goHistogram(data []float64, lo, hi float64, bins int) []float64 {
+	counts := make([]float64, bins)
+	for _, x := range data {
+		if math.IsNaN(x) {
+			continue
+		}
+		b := 0
+		if hi > lo {
+			b = int((x - lo) * (float64(bins) / (hi - lo)))
+		}
+		if b < 0 {
+			b = 0
+		}
+		if b >= bins {
+			b = bins - 1
+		}
+		counts[b]++
+	}
+	return counts
+}
+
+// goMatmul multiplies row-major a (m x k) by b (k x n).
+func goMatmul(a, b []float64, m, k, n int) []float64 {
+	out := make([]float64, m*n)
+	for i := 0; i < m; i++ {
+		for j := 0; j < n; j++ {
+			var s float64
+			for p := 0; p < k; p++ {
+				s += a[i*k+p] * b[p*n+j]
+			}
+			out[i*n+j] = s
+		}
+	}
+	return out
+}
+
+// goDFT is the O(n^2) forward DFT of interleaved (re, im) samples.
+func goDFT(x []float64) []float64 {
+	n := len(x) / 2
+	out := make([]float64, len(x))
+	for k := 0; k < n; k++ {
+		var re, im float64
+		for j := 0; j < n; j++ {
+			s, c := math.Sincos(-2 * math.Pi * float64(k*j) / float64(n))
+			re += x[2*j]*c - x[2*j+1]*s
+			im += x[2*j]*s + x[2*j+1]*c
+		}
+		out[2*k], out[2*k+1] = re, im
+	}
+	return out
+}
+
+// goRollingStd is the two-pass population stddev of each length-window slice.
+func goRollingStd(data []float64, window int) []float64 {
+	out := make([]float64, len(data)-window+1)
+	for i := range out {
+		w := data[i : i+window]
+		mean := goSum(w) / float64(window)
+		var m2 float64
+		for _, v := range w {
+			m2 += (v - mean) * (v - mean)
+		}
+		out[i] = math.Sqrt(m2 / float64(window))
+	}
+	return out
+}
+
 // --- Correctness Tests ---
 
 func testSumCorrectness(t *testing.T, runtime WasmRuntime) {
@@ -112,6 +196,530 @@ func TestDotCorrectness_Rust(t *testing.T)   { testDotCorrectness(t, RuntimeRust
 func TestDotCorrectness_TinyGo(t *testing.T) { testDotCorrectness(t, RuntimeTinyGo) }
 func TestDotCorrectness_C(t *testing.T)      { testDotCorrectness(t, RuntimeC) }
 
+// --- Rust Export Tests ---
+//
+// These cover exports only the Rust module provides, so they call through
+// callExport rather than a WasmVectorOps method.
+
+// callExport calls the named export, skipping the test if the module
+// doesn't provide it.
+func callExport(t *testing.T, ops *WasmVectorOps, name string, args ...interface{}) interface{} {
+	t.Helper()
+	fn := ops.instance.GetFunc(ops.store, name)
+	if fn == nil {
+		t.Skipf("module does not export '%s'", name)
+	}
+	result, err := fn.Call(ops.store, args...)
+	if err != nil {
+		t.Fatalf("%s failed: %v", name, err)
+	}
+	return result
+}
+
+// readWasm copies n float64 values out of WASM memory at offset.
+func readWasm(ops *WasmVectorOps, offset uint32, n int) []float64 {
+	out := make([]float64, n)
+	ops.copyFromWasm(out, offset)
+	return out
+}
+
+// Error codes reported by the Rust module's last_error.
+const (
+	errLenClamped = 1
+	errIndexRange = 2
+	errDimension  = 3
+	errInvalidArg = 6
+)
+
+// takeError returns the module's last_error and clears it.
+func takeError(t *testing.T, ops *WasmVectorOps) int32 {
+	t.Helper()
+	code := callExport(t, ops, "last_error").(int32)
+	callExport(t, ops, "clear_error")
+	return code
+}
+
+func testTopKCorrectness(t *testing.T, runtime WasmRuntime) {
+	ops := loadWasmOps(t, runtime)
+	defer ops.Close()
+
+	data := makeData(200)
+	ops.copyToWasm(data, ops.bufferAOffset)
+	want := append([]float64(nil), data...)
+	sort.Sort(sort.Reverse(sort.Float64Slice(want)))
+
+	for _, k := range []int{1, 10, len(data)} {
+		n := callExport(t, ops, "top_k", int32(k), int32(len(data))).(int32)
+		if int(n) != k {
+			t.Fatalf("%s top_k(%d) returned %d", runtime, k, n)
+		}
+		got := readWasm(ops, ops.resultOffset, k)
+		for i := range got {
+			if got[i] != want[i] {
+				t.Fatalf("%s top_k(%d)[%d]: Go=%v, WASM=%v", runtime, k, i, want[i], got[i])
+			}
+		}
+	}
+
+	// k is cut to len, and top_k only reads BUFFER_A.
+	if n := callExport(t, ops, "top_k", int32(len(data)+50), int32(len(data))).(int32); int(n) != len(data) {
+		t.Errorf("%s top_k(len+50) returned %d, want %d", runtime, n, len(data))
+	}
+	if n := callExport(t, ops, "top_k", int32(0), int32(len(data))).(int32); n != 0 {
+		t.Errorf("%s top_k(0) returned %d, want 0", runtime, n)
+	}
+	got := readWasm(ops, ops.bufferAOffset, len(data))
+	for i := range data {
+		if got[i] != data[i] {
+			t.Fatalf("%s top_k modified BUFFER_A[%d]", runtime, i)
+		}
+	}
+}
+
+func TestTopKCorrectness_Rust(t *testing.T) { testTopKCorrectness(t, RuntimeRust) }
+
+func testPercentileCorrectness(t *testing.T, runtime WasmRuntime) {
+	ops := loadWasmOps(t, runtime)
+	defer ops.Close()
+
+	data := makeData(101)
+	ops.copyToWasm(data, ops.bufferAOffset)
+	for _, p := range []float64{0, 12.5, 50, 90, 100} {
+		want := goPercentile(data, p)
+		got := callExport(t, ops, "percentile", p, int32(len(data))).(float64)
+		if math.Abs(got-want) > 1e-9 {
+			t.Errorf("%s percentile(%v) mismatch: Go=%v, WASM=%v", runtime, p, want, got)
+		}
+	}
+
+	// percentile selects in RESULT; BUFFER_A keeps the caller's order.
+	got := readWasm(ops, ops.bufferAOffset, len(data))
+	for i := range data {
+		if got[i] != data[i] {
+			t.Fatalf("%s percentile reordered BUFFER_A[%d]", runtime, i)
+		}
+	}
+
+	if got := callExport(t, ops, "percentile", 101.0, int32(len(data))).(float64); !math.IsNaN(got) {
+		t.Errorf("%s percentile(101): got %v, want NaN", runtime, got)
+	}
+	if got := callExport(t, ops, "percentile", 50.0, int32(0)).(float64); !math.IsNaN(got) {
+		t.Errorf("%s percentile of empty input: got %v, want NaN", runtime, got)
+	}
+}
+
+func TestPercentileCorrectness_Rust(t *testing.T) { testPercentileCorrectness(t, RuntimeRust) }
+
+func testHistogramCorrectness(t *testing.T, runtime WasmRuntime) {
+	ops := loadWasmOps(t, runtime)
+	defer ops.Close()
+
+	const bins = 10
+	data := makeData(1000)
+	ops.copyToWasm(data, ops.bufferAOffset)
+	callExport(t, ops, "histogram", 0.0, 100.0, int32(bins), int32(len(data)))
+
+	want := goHistogram(data, 0, 100, bins)
+	got := readWasm(ops, ops.resultOffset, bins)
+	for b := range want {
+		if got[b] != want[b] {
+			t.Errorf("%s histogram bucket %d: Go=%v, WASM=%v", runtime, b, want[b], got[b])
+		}
+	}
+
+	// Bucket edges: max goes in the last bucket, out-of-range values are
+	// clamped into the end buckets, and NaN is dropped.
+	edges := []float64{-5, 0, 9.99, 10, 50, 100, 150, math.NaN()}
+	ops.copyToWasm(edges, ops.bufferAOffset)
+	callExport(t, ops, "histogram", 0.0, 100.0, int32(bins), int32(len(edges)))
+	want = []float64{3, 1, 0, 0, 0, 1, 0, 0, 0, 2}
+	got = readWasm(ops, ops.resultOffset, bins)
+	for b := range want {
+		if got[b] != want[b] {
+			t.Errorf("%s histogram edge bucket %d: want %v, got %v", runtime, b, want[b], got[b])
+		}
+	}
+}
+
+func TestHistogramCorrectness_Rust(t *testing.T) { testHistogramCorrectness(t, RuntimeRust) }
+
+func testMatmulCorrectness(t *testing.T, runtime WasmRuntime) {
+	ops := loadWasmOps(t, runtime)
+	defer ops.Close()
+
+	const m, k, n = 7, 5, 9
+	a := makeData(m * k)
+	b := makeData(k * n)
+	ops.copyToWasm(a, ops.bufferAOffset)
+	ops.copyToWasm(b, ops.bufferBOffset)
+	callExport(t, ops, "matmul", int32(m), int32(k), int32(n))
+
+	want := goMatmul(a, b, m, k, n)
+	got := readWasm(ops, ops.resultOffset, m*n)
+	for i := range want {
+		if math.Abs(got[i]-want[i]) > 1e-6 {
+			t.Errorf("%s matmul element (%d, %d): Go=%v, WASM=%v", runtime, i/n, i%n, want[i], got[i])
+		}
+	}
+
+	// A and B fit but the m x n product doesn't: RESULT is left alone.
+	sentinel := []float64{1, 2, 3, 4}
+	ops.copyToWasm(sentinel, ops.resultOffset)
+	c := int32(ops.Capacity())
+	callExport(t, ops, "matmul", c, int32(1), c)
+	got = readWasm(ops, ops.resultOffset, len(sentinel))
+	for i := range sentinel {
+		if got[i] != sentinel[i] {
+			t.Fatalf("%s oversized matmul wrote RESULT[%d]", runtime, i)
+		}
+	}
+}
+
+func TestMatmulCorrectness_Rust(t *testing.T) { testMatmulCorrectness(t, RuntimeRust) }
+
+func testTransposeCorrectness(t *testing.T, runtime WasmRuntime) {
+	ops := loadWasmOps(t, runtime)
+	defer ops.Close()
+
+	// Shapes that end partway through a 32x32 tile on both axes, plus the
+	// single-row and single-column cases.
+	for _, shape := range [][2]int{{37, 70}, {1, 45}, {45, 1}, {32, 64}} {
+		rows, cols := shape[0], shape[1]
+		m := makeData(rows * cols)
+		ops.copyToWasm(m, ops.bufferAOffset)
+		callExport(t, ops, "transpose", int32(rows), int32(cols))
+
+		got := readWasm(ops, ops.resultOffset, rows*cols)
+		for r := 0; r < rows; r++ {
+			for c := 0; c < cols; c++ {
+				if got[c*rows+r] != m[r*cols+c] {
+					t.Fatalf("%s transpose %dx%d: element (%d, %d) misplaced", runtime, rows, cols, r, c)
+				}
+			}
+		}
+	}
+}
+
+func TestTransposeCorrectness_Rust(t *testing.T) { testTransposeCorrectness(t, RuntimeRust) }
+
+func testReserveSumAtCorrectness(t *testing.T, runtime WasmRuntime) {
+	ops := loadWasmOps(t, runtime)
+	defer ops.Close()
+
+	// Larger than the static buffers, which is what the dynamic region is for.
+	n := ops.Capacity() + 1000
+	offset := uint32(callExport(t, ops, "reserve", int32(n)).(int32))
+	if offset == 0 {
+		t.Fatalf("%s reserve(%d) failed to grow memory", runtime, n)
+	}
+	data := makeData(n)
+	ops.copyToWasm(data, offset)
+
+	want := goSum(data)
+	got := callExport(t, ops, "sum_at", int32(offset), int32(n)).(float64)
+	if math.Abs(got-want) > 1e-9*math.Abs(want) {
+		t.Errorf("%s sum_at mismatch: Go=%v, WASM=%v", runtime, want, got)
+	}
+
+	// A region that is already big enough is reused, not moved.
+	if again := uint32(callExport(t, ops, "reserve", int32(10)).(int32)); again != offset {
+		t.Errorf("%s reserve(10) moved the region: %d, want %d", runtime, again, offset)
+	}
+
+	takeError(t, ops)
+	callExport(t, ops, "sum_at", int32(offset), int32(2*n))
+	if code := takeError(t, ops); code != errLenClamped {
+		t.Errorf("%s sum_at past the region: last_error=%d, want %d", runtime, code, errLenClamped)
+	}
+	if got := callExport(t, ops, "sum_at", int32(ops.bufferAOffset), int32(1)).(float64); got != 0 {
+		t.Errorf("%s sum_at outside the region: got %v, want 0", runtime, got)
+	}
+	if code := takeError(t, ops); code != errIndexRange {
+		t.Errorf("%s sum_at outside the region: last_error=%d, want %d", runtime, code, errIndexRange)
+	}
+}
+
+func TestReserveSumAtCorrectness_Rust(t *testing.T) { testReserveSumAtCorrectness(t, RuntimeRust) }
+
+func testBatchedDotCorrectness(t *testing.T, runtime WasmRuntime) {
+	ops := loadWasmOps(t, runtime)
+	defer ops.Close()
+
+	const d, n = 8, 50
+	query := makeData(d)
+	rows := makeData(d * n)
+	ops.copyToWasm(query, ops.bufferAOffset)
+	ops.copyToWasm(rows, ops.bufferBOffset)
+	callExport(t, ops, "batched_dot", int32(d), int32(n))
+
+	got := readWasm(ops, ops.resultOffset, n)
+	for r := 0; r < n; r++ {
+		want := goDot(query, rows[r*d:(r+1)*d])
+		if math.Abs(got[r]-want) > 1e-6 {
+			t.Errorf("%s batched_dot row %d: Go=%v, WASM=%v", runtime, r, want, got[r])
+		}
+	}
+
+	// With d == 0, d * n is 0 for any n; n itself must still fit RESULT.
+	ops.copyToWasm(query, ops.bufferAOffset)
+	takeError(t, ops)
+	callExport(t, ops, "batched_dot", int32(0), int32(2*ops.Capacity()))
+	if code := takeError(t, ops); code != errDimension {
+		t.Errorf("%s batched_dot(0, 2*capacity): last_error=%d, want %d", runtime, code, errDimension)
+	}
+	got = readWasm(ops, ops.bufferAOffset, d)
+	for i := range query {
+		if got[i] != query[i] {
+			t.Fatalf("%s batched_dot(0, 2*capacity) overwrote BUFFER_A[%d]", runtime, i)
+		}
+	}
+}
+
+func TestBatchedDotCorrectness_Rust(t *testing.T) { testBatchedDotCorrectness(t, RuntimeRust) }
+
+func testFFTCorrectness(t *testing.T, runtime WasmRuntime) {
+	ops := loadWasmOps(t, runtime)
+	defer ops.Close()
+
+	const log2n = 6
+	x := makeData(2 << log2n)
+	ops.copyToWasm(x, ops.bufferAOffset)
+	callExport(t, ops, "fft", int32(log2n))
+
+	want := goDFT(x)
+	got := readWasm(ops, ops.bufferAOffset, len(x))
+	for i := range want {
+		if math.Abs(got[i]-want[i]) > 1e-6 {
+			t.Fatalf("%s fft mismatch at %d: Go=%v, WASM=%v", runtime, i, want[i], got[i])
+		}
+	}
+
+	callExport(t, ops, "ifft", int32(log2n))
+	got = readWasm(ops, ops.bufferAOffset, len(x))
+	for i := range x {
+		if math.Abs(got[i]-x[i]) > 1e-9 {
+			t.Fatalf("%s ifft(fft(x)) mismatch at %d: want %v, got %v", runtime, i, x[i], got[i])
+		}
+	}
+
+	// 2 * 2^log2n values must fit BUFFER_A.
+	takeError(t, ops)
+	big := int32(math.Ceil(math.Log2(float64(ops.Capacity()))))
+	callExport(t, ops, "fft", big)
+	if code := takeError(t, ops); code != errDimension {
+		t.Errorf("%s fft(%d): last_error=%d, want %d", runtime, big, code, errDimension)
+	}
+}
+
+func TestFFTCorrectness_Rust(t *testing.T) { testFFTCorrectness(t, RuntimeRust) }
+
+func testRollingStdCorrectness(t *testing.T, runtime WasmRuntime) {
+	ops := loadWasmOps(t, runtime)
+	defer ops.Close()
+
+	// Long enough for several of the every-window-steps recomputes.
+	const window = 7
+	data := makeData(500)
+	ops.copyToWasm(data, ops.bufferAOffset)
+	callExport(t, ops, "rolling_std", int32(window), int32(len(data)))
+
+	want := goRollingStd(data, window)
+	got := readWasm(ops, ops.resultOffset, len(want))
+	for i := range want {
+		if math.Abs(got[i]-want[i]) > 1e-9 {
+			t.Fatalf("%s rolling_std mismatch at %d: Go=%v, WASM=%v", runtime, i, want[i], got[i])
+		}
+	}
+
+	for _, w := range []int{0, len(data) + 1} {
+		takeError(t, ops)
+		callExport(t, ops, "rolling_std", int32(w), int32(len(data)))
+		if code := takeError(t, ops); code != errDimension {
+			t.Errorf("%s rolling_std(window=%d): last_error=%d, want %d", runtime, w, code, errDimension)
+		}
+	}
+}
+
+func TestRollingStdCorrectness_Rust(t *testing.T) { testRollingStdCorrectness(t, RuntimeRust) }
+
+func testMatvecCorrectness(t *testing.T, runtime WasmRuntime) {
+	ops := loadWasmOps(t, runtime)
+	defer ops.Close()
+
+	const rows, cols = 40, 12
+	m := makeData(rows * cols)
+	v := makeData(cols)
+	ops.copyToWasm(m, ops.bufferAOffset)
+	ops.copyToWasm(v, ops.bufferBOffset)
+	callExport(t, ops, "matvec", int32(rows), int32(cols))
+
+	got := readWasm(ops, ops.resultOffset, rows)
+	for r := 0; r < rows; r++ {
+		want := goDot(m[r*cols:(r+1)*cols], v)
+		if math.Abs(got[r]-want) > 1e-6 {
+			t.Errorf("%s matvec row %d: Go=%v, WASM=%v", runtime, r, want, got[r])
+		}
+	}
+
+	// With cols == 0, rows * cols is 0 for any rows; rows must still fit RESULT.
+	ops.copyToWasm(m[:cols], ops.bufferAOffset)
+	takeError(t, ops)
+	callExport(t, ops, "matvec", int32(2*ops.Capacity()), int32(0))
+	if code := takeError(t, ops); code != errDimension {
+		t.Errorf("%s matvec(2*capacity, 0): last_error=%d, want %d", runtime, code, errDimension)
+	}
+	got = readWasm(ops, ops.bufferAOffset, cols)
+	for i := range got {
+		if got[i] != m[i] {
+			t.Fatalf("%s matvec(2*capacity, 0) overwrote BUFFER_A[%d]", runtime, i)
+		}
+	}
+}
+
+func TestMatvecCorrectness_Rust(t *testing.T) { testMatvecCorrectness(t, RuntimeRust) }
+
+func testUpsampleLinearCorrectness(t *testing.T, runtime WasmRuntime) {
+	ops := loadWasmOps(t, runtime)
+	defer ops.Close()
+
+	const inLen, outLen = 5, 13
+	x := makeData(inLen)
+	ops.copyToWasm(x, ops.bufferAOffset)
+	callExport(t, ops, "upsample_linear", int32(outLen), int32(inLen))
+
+	got := readWasm(ops, ops.resultOffset, outLen)
+	step := float64(inLen-1) / float64(outLen-1)
+	for j := 0; j < outLen; j++ {
+		pos := float64(j) * step
+		i := int(pos)
+		want := x[inLen-1]
+		if i+1 < inLen {
+			want = x[i] + (pos-float64(i))*(x[i+1]-x[i])
+		}
+		if math.Abs(got[j]-want) > 1e-9 {
+			t.Errorf("%s upsample_linear[%d]: Go=%v, WASM=%v", runtime, j, want, got[j])
+		}
+	}
+	if got[0] != x[0] || got[outLen-1] != x[inLen-1] {
+		t.Errorf("%s upsample_linear endpoints not aligned: got %v, %v", runtime, got[0], got[outLen-1])
+	}
+
+	takeError(t, ops)
+	callExport(t, ops, "upsample_linear", int32(outLen), int32(0))
+	if code := takeError(t, ops); code != errDimension {
+		t.Errorf("%s upsample_linear with in_len 0: last_error=%d, want %d", runtime, code, errDimension)
+	}
+	callExport(t, ops, "upsample_linear", int32(ops.Capacity()+1), int32(inLen))
+	if code := takeError(t, ops); code != errDimension {
+		t.Errorf("%s upsample_linear past capacity: last_error=%d, want %d", runtime, code, errDimension)
+	}
+}
+
+func TestUpsampleLinearCorrectness_Rust(t *testing.T) { testUpsampleLinearCorrectness(t, RuntimeRust) }
+
+func testHistogramAutoCorrectness(t *testing.T, runtime WasmRuntime) {
+	ops := loadWasmOps(t, runtime)
+	defer ops.Close()
+
+	const bins = 16
+	data := makeData(1000)
+	ops.copyToWasm(data, ops.bufferAOffset)
+	callExport(t, ops, "histogram_auto", int32(bins), int32(len(data)))
+
+	lo, hi := data[0], data[0]
+	for _, x := range data {
+		lo, hi = math.Min(lo, x), math.Max(hi, x)
+	}
+	got := readWasm(ops, ops.resultOffset, bins+2)
+	if got[bins] != lo || got[bins+1] != hi {
+		t.Fatalf("%s histogram_auto range: want [%v, %v], got [%v, %v]", runtime, lo, hi, got[bins], got[bins+1])
+	}
+	want := goHistogram(data, lo, hi, bins)
+	for b := range want {
+		if got[b] != want[b] {
+			t.Errorf("%s histogram_auto bucket %d: Go=%v, WASM=%v", runtime, b, want[b], got[b])
+		}
+	}
+
+	// Constant data has no spread, so everything lands in bucket 0.
+	constant := []float64{4, 4, 4}
+	ops.copyToWasm(constant, ops.bufferAOffset)
+	callExport(t, ops, "histogram_auto", int32(bins), int32(len(constant)))
+	if got := readWasm(ops, ops.resultOffset, 2); got[0] != 3 || got[1] != 0 {
+		t.Errorf("%s histogram_auto of constant data: buckets 0-1 = %v, want [3 0]", runtime, got)
+	}
+
+	takeError(t, ops)
+	callExport(t, ops, "histogram_auto", int32(ops.Capacity()-1), int32(len(constant)))
+	if code := takeError(t, ops); code != errDimension {
+		t.Errorf("%s histogram_auto(capacity-1): last_error=%d, want %d", runtime, code, errDimension)
+	}
+}
+
+func TestHistogramAutoCorrectness_Rust(t *testing.T) { testHistogramAutoCorrectness(t, RuntimeRust) }
+
+func testWinsorizeCorrectness(t *testing.T, runtime WasmRuntime) {
+	ops := loadWasmOps(t, runtime)
+	defer ops.Close()
+
+	data := makeData(100)
+	ops.copyToWasm(data, ops.bufferAOffset)
+	callExport(t, ops, "winsorize", 5.0, 95.0, int32(len(data)))
+
+	lo, hi := goPercentile(data, 5), goPercentile(data, 95)
+	got := readWasm(ops, ops.bufferAOffset, len(data))
+	for i, x := range data {
+		want := math.Min(math.Max(x, lo), hi)
+		if math.Abs(got[i]-want) > 1e-9 {
+			t.Errorf("%s winsorize[%d]: Go=%v, WASM=%v", runtime, i, want, got[i])
+		}
+	}
+
+	// lower_pct must be below upper_pct; otherwise nothing changes.
+	ops.copyToWasm(data, ops.bufferAOffset)
+	takeError(t, ops)
+	callExport(t, ops, "winsorize", 95.0, 5.0, int32(len(data)))
+	if code := takeError(t, ops); code != errInvalidArg {
+		t.Errorf("%s winsorize(95, 5): last_error=%d, want %d", runtime, code, errInvalidArg)
+	}
+	got = readWasm(ops, ops.bufferAOffset, len(data))
+	for i := range data {
+		if got[i] != data[i] {
+			t.Fatalf("%s winsorize(95, 5) modified BUFFER_A[%d]", runtime, i)
+		}
+	}
+}
+
+func TestWinsorizeCorrectness_Rust(t *testing.T) { testWinsorizeCorrectness(t, RuntimeRust) }
+
+func testWinsorizeNaNCorrectness(t *testing.T, runtime WasmRuntime) {
+	ops := loadWasmOps(t, runtime)
+	defer ops.Close()
+
+	// NaNs fill the top ranks; the percentiles must come from the numbers
+	// alone, or hi would be NaN and the 100 would never be clamped.
+	nums := []float64{1, 2, 3, 4, 5, 6, 7, 8, 9, 100}
+	data := append(append([]float64(nil), nums...), math.NaN(), math.NaN())
+	ops.copyToWasm(data, ops.bufferAOffset)
+	callExport(t, ops, "winsorize", 10.0, 90.0, int32(len(data)))
+
+	lo, hi := goPercentile(nums, 10), goPercentile(nums, 90)
+	got := readWasm(ops, ops.bufferAOffset, len(data))
+	for i, x := range nums {
+		want := math.Min(math.Max(x, lo), hi)
+		if math.Abs(got[i]-want) > 1e-9 {
+			t.Errorf("%s winsorize with NaN [%d]: Go=%v, WASM=%v", runtime, i, want, got[i])
+		}
+	}
+	for i := len(nums); i < len(data); i++ {
+		if !math.IsNaN(got[i]) {
+			t.Errorf("%s winsorize with NaN: element %d = %v, want NaN", runtime, i, got[i])
+		}
+	}
+}
+
+func TestWinsorizeNaNCorrectness_Rust(t *testing.T) { testWinsorizeNaNCorrectness(t, RuntimeRust) }
+
 // --- Benchmarks ---
 
 // Benchmark helpers
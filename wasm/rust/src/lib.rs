@@ -9,52 +9,199 @@
 #![no_std]
 
 use core::cell::UnsafeCell;
+use core::cmp::Ordering;
 use core::ptr::addr_of;
 
-// Pre-allocated buffer capacity (100K f64 elements = 800KB per buffer)
+// Pre-allocated buffer capacity, selected by Cargo feature (default 100K
+// f64 elements = 800KB per buffer). Hosts discover it via get_capacity().
+#[cfg(any(
+    all(feature = "cap-4k", feature = "cap-100k"),
+    all(feature = "cap-4k", feature = "cap-1m"),
+    all(feature = "cap-100k", feature = "cap-1m"),
+))]
+compile_error!("enable at most one of the cap-4k, cap-100k, cap-1m features");
+
+#[cfg(feature = "cap-4k")]
+const CAPACITY: usize = 4_096;
+#[cfg(feature = "cap-1m")]
+const CAPACITY: usize = 1_000_000;
+#[cfg(not(any(feature = "cap-4k", feature = "cap-1m")))]
 const CAPACITY: usize = 100_000;
 
-// Wrapper for static mutable buffers - safe in single-threaded WASM
-#[repr(transparent)]
-struct StaticBuffer(UnsafeCell<[f64; CAPACITY]>);
+// Wrapper for static mutable buffers - safe in single-threaded WASM.
+// Cache-line aligned so every buffer starts on a 64-byte boundary, which
+// also keeps the v128 loads in the SIMD paths aligned.
+#[repr(C, align(64))]
+struct StaticBuffer<T>(UnsafeCell<[T; CAPACITY]>);
+
+const BUFFER_ALIGN: usize = 64;
+const _: () = assert!(align_of::<StaticBuffer<i8>>() == BUFFER_ALIGN);
 
 // SAFETY: WASM is single-threaded, so this is safe
-unsafe impl Sync for StaticBuffer {}
+unsafe impl<T> Sync for StaticBuffer<T> {}
 
-impl StaticBuffer {
-    const fn new() -> Self {
-        StaticBuffer(UnsafeCell::new([0.0; CAPACITY]))
+impl<T: Copy> StaticBuffer<T> {
+    const fn new(zero: T) -> Self {
+        StaticBuffer(UnsafeCell::new([zero; CAPACITY]))
     }
 
     #[inline]
-    fn as_ptr(&self) -> *const f64 {
-        self.0.get() as *const f64
+    fn as_ptr(&self) -> *const T {
+        self.0.get() as *const T
     }
 
     #[inline]
-    fn as_mut_ptr(&self) -> *mut f64 {
-        self.0.get() as *mut f64
+    fn as_mut_ptr(&self) -> *mut T {
+        self.0.get() as *mut T
     }
 
     #[inline]
-    unsafe fn get(&self, i: usize) -> f64 {
+    unsafe fn get(&self, i: usize) -> T {
         *self.as_ptr().add(i)
     }
 
     #[inline]
-    unsafe fn set(&self, i: usize, val: f64) {
+    unsafe fn set(&self, i: usize, val: T) {
         *self.as_mut_ptr().add(i) = val;
     }
+
+    #[inline]
+    unsafe fn fill(&self, len: usize, val: T) {
+        for i in 0..len {
+            self.set(i, val);
+        }
+    }
+
+    #[inline]
+    unsafe fn copy_from(&self, src: &StaticBuffer<T>, len: usize) {
+        core::ptr::copy(src.as_ptr(), self.as_mut_ptr(), len);
+    }
+
+    // Same interior mutability as set(); the caller must not hold any other
+    // reference into the first len elements while the slice is alive
+    #[allow(clippy::mut_from_ref)]
+    #[inline]
+    unsafe fn as_mut_slice(&self, len: usize) -> &mut [T] {
+        core::slice::from_raw_parts_mut(self.as_mut_ptr(), len)
+    }
+}
+
+// Wrapper for static mutable scalars (PRNG state etc.) - same
+// single-threaded assumption as StaticBuffer
+struct StaticCell<T>(UnsafeCell<T>);
+
+// SAFETY: WASM is single-threaded, so this is safe
+unsafe impl<T> Sync for StaticCell<T> {}
+
+impl<T: Copy> StaticCell<T> {
+    const fn new(val: T) -> Self {
+        StaticCell(UnsafeCell::new(val))
+    }
+
+    #[inline]
+    fn get(&self) -> T {
+        unsafe { *self.0.get() }
+    }
+
+    #[inline]
+    fn set(&self, val: T) {
+        unsafe { *self.0.get() = val }
+    }
 }
 
 // Static buffers - allocated once, stable addresses
-static BUFFER_A: StaticBuffer = StaticBuffer::new();
-static BUFFER_B: StaticBuffer = StaticBuffer::new();
-static RESULT: StaticBuffer = StaticBuffer::new();
+static BUFFER_A: StaticBuffer<f64> = StaticBuffer::new(0.0);
+static BUFFER_B: StaticBuffer<f64> = StaticBuffer::new(0.0);
+static RESULT: StaticBuffer<f64> = StaticBuffer::new(0.0);
+
+// f32 buffers - same element count, half the bytes. Lets hosts with
+// single-precision data skip the widening copy.
+static BUFFER_A_F32: StaticBuffer<f32> = StaticBuffer::new(0.0);
+static BUFFER_B_F32: StaticBuffer<f32> = StaticBuffer::new(0.0);
+static RESULT_F32: StaticBuffer<f32> = StaticBuffer::new(0.0);
+
+// i32 buffers for integer IDs, counts and bitmasks, handled without a lossy
+// trip through f64. BUFFER_I32 plays the role of BUFFER_A.
+static BUFFER_I32: StaticBuffer<i32> = StaticBuffer::new(0);
+static BUFFER_B_I32: StaticBuffer<i32> = StaticBuffer::new(0);
+static RESULT_I32: StaticBuffer<i32> = StaticBuffer::new(0);
+
+// int8 quantized values, written by quantize_i8 and read by dequantize_i8
+static BUFFER_I8: StaticBuffer<i8> = StaticBuffer::new(0);
+
+// Element indices into the f64 buffers, used by gather and scatter
+static INDEX: StaticBuffer<u32> = StaticBuffer::new(0);
+
+// Host-visible error register. Ops that truncate or reject their input set
+// a code here and otherwise behave as documented; nothing clears it except
+// clear_error, so a host can run a batch of calls and check once. A later
+// error overwrites an earlier one.
+const ERR_NONE: u32 = 0;
+const ERR_LEN_CLAMPED: u32 = 1; // a length was cut to fit a buffer
+const ERR_INDEX_RANGE: u32 = 2; // an index or offset fell outside its buffer
+const ERR_DIMENSION: u32 = 3; // shapes don't fit or don't agree; op did nothing
+const ERR_ZERO_DIVISOR: u32 = 4; // a computed divisor was zero; op did nothing
+const ERR_OVERFLOW: u32 = 5; // an integer result didn't fit its type
+const ERR_INVALID_ARG: u32 = 6; // an argument was outside its domain; op did nothing
+static LAST_ERROR: StaticCell<u32> = StaticCell::new(ERR_NONE);
+
+fn set_error(code: u32) {
+    LAST_ERROR.set(code);
+}
+
+// len clamped to CAPACITY, flagging ERR_LEN_CLAMPED when it had to be cut.
+fn clamp_len(len: u32) -> usize {
+    if len as usize > CAPACITY {
+        set_error(ERR_LEN_CLAMPED);
+        CAPACITY
+    } else {
+        len as usize
+    }
+}
+
+// SplitMix64 PRNG state. Any seed (including 0) gives a full-period stream.
+const DEFAULT_SEED: u64 = 0x853C_49E6_748F_EA9B;
+static RNG_STATE: StaticCell<u64> = StaticCell::new(DEFAULT_SEED);
+
+fn next_u64() -> u64 {
+    let s = RNG_STATE.get().wrapping_add(0x9E37_79B9_7F4A_7C15);
+    RNG_STATE.set(s);
+    let mut z = s;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+// Uniform in [0, 1) with 53 bits of randomness
+fn next_f64() -> f64 {
+    (next_u64() >> 11) as f64 * (1.0 / (1u64 << 53) as f64)
+}
+
+// Ascending total order for sorting/selection: NaNs sort after every
+// number (and compare equal to each other), -0.0 and 0.0 tie.
+fn cmp_nan_last(a: &f64, b: &f64) -> Ordering {
+    match (a.is_nan(), b.is_nan()) {
+        (false, false) => a.partial_cmp(b).unwrap_or(Ordering::Equal),
+        (false, true) => Ordering::Less,
+        (true, false) => Ordering::Greater,
+        (true, true) => Ordering::Equal,
+    }
+}
+
+// Fused a * b + c with a single rounding. core has no f64::mul_add under
+// no_std, so this goes through libm::fma. Core wasm32 has no scalar FMA
+// instruction, so it buys accuracy rather than speed there: in node,
+// fma_accumulate over 100K elements runs 10-15x slower than with a separate
+// multiply and add. The throughput kernels (dot_simd, dot4, matvec, matmul)
+// multiply and add separately instead.
+#[inline]
+fn mul_add(a: f64, b: f64, c: f64) -> f64 {
+    libm::fma(a, b, c)
+}
 
 #[no_mangle]
 pub extern "C" fn sum(len: u32) -> f64 {
-    let len = (len as usize).min(CAPACITY);
+    let len = clamp_len(len);
     let mut s = 0.0;
     unsafe {
         for i in 0..len {
@@ -66,7 +213,7 @@ pub extern "C" fn sum(len: u32) -> f64 {
 
 #[no_mangle]
 pub extern "C" fn dot(len: u32) -> f64 {
-    let len = (len as usize).min(CAPACITY);
+    let len = clamp_len(len);
     let mut d = 0.0;
     unsafe {
         for i in 0..len {
@@ -78,7 +225,7 @@ pub extern "C" fn dot(len: u32) -> f64 {
 
 #[no_mangle]
 pub extern "C" fn mul(len: u32) {
-    let len = (len as usize).min(CAPACITY);
+    let len = clamp_len(len);
     unsafe {
         for i in 0..len {
             RESULT.set(i, BUFFER_A.get(i) * BUFFER_B.get(i));
@@ -86,9 +233,99 @@ pub extern "C" fn mul(len: u32) {
     }
 }
 
+#[no_mangle]
+pub extern "C" fn add(len: u32) {
+    let len = clamp_len(len);
+    unsafe {
+        for i in 0..len {
+            RESULT.set(i, BUFFER_A.get(i) + BUFFER_B.get(i));
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn sub(len: u32) {
+    let len = clamp_len(len);
+    unsafe {
+        for i in 0..len {
+            RESULT.set(i, BUFFER_A.get(i) - BUFFER_B.get(i));
+        }
+    }
+}
+
+// Plain IEEE-754 division: x/0.0 gives +/-inf, 0.0/0.0 gives NaN.
+// Kept branch-free so the loop still auto-vectorizes.
+#[no_mangle]
+pub extern "C" fn div(len: u32) {
+    let len = clamp_len(len);
+    unsafe {
+        for i in 0..len {
+            RESULT.set(i, BUFFER_A.get(i) / BUFFER_B.get(i));
+        }
+    }
+}
+
+// Like div, but writes `fallback` wherever the divisor is exactly +/-0.0.
+// Subnormal divisors are not zero and divide normally (usually to +/-inf).
+#[no_mangle]
+pub extern "C" fn div_safe(fallback: f64, len: u32) {
+    let len = clamp_len(len);
+    unsafe {
+        for i in 0..len {
+            let d = BUFFER_B.get(i);
+            let q = if d == 0.0 {
+                fallback
+            } else {
+                BUFFER_A.get(i) / d
+            };
+            RESULT.set(i, q);
+        }
+    }
+}
+
+// RESULT[i] = max(A[i], B[i]). NaN in either input gives NaN (unlike
+// f64::max, which returns the other operand). Of -0.0 and 0.0, B's wins.
+#[no_mangle]
+pub extern "C" fn max_ab(len: u32) {
+    let len = clamp_len(len);
+    unsafe {
+        for i in 0..len {
+            let (a, b) = (BUFFER_A.get(i), BUFFER_B.get(i));
+            let m = if a.is_nan() || b.is_nan() {
+                f64::NAN
+            } else if a > b {
+                a
+            } else {
+                b
+            };
+            RESULT.set(i, m);
+        }
+    }
+}
+
+// RESULT[i] = min(A[i], B[i]), with the same NaN and signed-zero rules as
+// max_ab.
+#[no_mangle]
+pub extern "C" fn min_ab(len: u32) {
+    let len = clamp_len(len);
+    unsafe {
+        for i in 0..len {
+            let (a, b) = (BUFFER_A.get(i), BUFFER_B.get(i));
+            let m = if a.is_nan() || b.is_nan() {
+                f64::NAN
+            } else if a < b {
+                a
+            } else {
+                b
+            };
+            RESULT.set(i, m);
+        }
+    }
+}
+
 #[no_mangle]
 pub extern "C" fn scale(scalar: f64, len: u32) {
-    let len = (len as usize).min(CAPACITY);
+    let len = clamp_len(len);
     unsafe {
         for i in 0..len {
             BUFFER_A.set(i, BUFFER_A.get(i) * scalar);
@@ -96,9 +333,71 @@ pub extern "C" fn scale(scalar: f64, len: u32) {
     }
 }
 
+// scale, returning how many elements became non-finite: finite inputs that
+// overflowed to +/-inf or turned NaN (e.g. 0.0 * inf). Elements that were
+// already inf or NaN are not counted.
+#[no_mangle]
+pub extern "C" fn scale_checked(scalar: f64, len: u32) -> u32 {
+    let len = clamp_len(len);
+    let mut n = 0;
+    unsafe {
+        for i in 0..len {
+            let x = BUFFER_A.get(i);
+            let y = x * scalar;
+            BUFFER_A.set(i, y);
+            n += (x.is_finite() && !y.is_finite()) as u32;
+        }
+    }
+    n
+}
+
+// Out-of-place scalar broadcasts: RESULT[i] = A[i] op scalar, leaving
+// BUFFER_A intact.
+#[no_mangle]
+pub extern "C" fn add_scalar_to_result(scalar: f64, len: u32) {
+    let len = clamp_len(len);
+    unsafe {
+        for i in 0..len {
+            RESULT.set(i, BUFFER_A.get(i) + scalar);
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn sub_scalar_to_result(scalar: f64, len: u32) {
+    let len = clamp_len(len);
+    unsafe {
+        for i in 0..len {
+            RESULT.set(i, BUFFER_A.get(i) - scalar);
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn mul_scalar_to_result(scalar: f64, len: u32) {
+    let len = clamp_len(len);
+    unsafe {
+        for i in 0..len {
+            RESULT.set(i, BUFFER_A.get(i) * scalar);
+        }
+    }
+}
+
+// BUFFER_A[i] *= BUFFER_B[i] in place: a diagonal scaling of A. Unlike mul,
+// RESULT is untouched.
+#[no_mangle]
+pub extern "C" fn scale_by_b(len: u32) {
+    let len = clamp_len(len);
+    unsafe {
+        for i in 0..len {
+            BUFFER_A.set(i, BUFFER_A.get(i) * BUFFER_B.get(i));
+        }
+    }
+}
+
 #[no_mangle]
 pub extern "C" fn sum_simd(len: u32) -> f64 {
-    let len = (len as usize).min(CAPACITY);
+    let len = clamp_len(len);
 
     #[cfg(target_feature = "simd128")]
     {
@@ -154,28 +453,3550 @@ pub extern "C" fn sum_simd(len: u32) -> f64 {
     }
 }
 
+// Dot product counterpart to sum_simd. Without simd128, four independent
+// accumulators break the single dependency chain of dot. Plain multiply and
+// add rather than mul_add: wasm32 has no scalar FMA, and the software fma
+// would cost far more than the chain it replaces.
 #[no_mangle]
-pub extern "C" fn get_buffer_a_offset() -> u32 {
-    addr_of!(BUFFER_A) as u32
+pub extern "C" fn dot_simd(len: u32) -> f64 {
+    let len = clamp_len(len);
+
+    #[cfg(target_feature = "simd128")]
+    {
+        use core::arch::wasm32::*;
+
+        unsafe {
+            let pa = BUFFER_A.as_ptr();
+            let pb = BUFFER_B.as_ptr();
+            let mut acc = f64x2_splat(0.0);
+            let mut i = 0;
+
+            while i + 1 < len {
+                let va = v128_load(pa.add(i) as *const v128);
+                let vb = v128_load(pb.add(i) as *const v128);
+                acc = f64x2_add(acc, f64x2_mul(va, vb));
+                i += 2;
+            }
+
+            let mut d = f64x2_extract_lane::<0>(acc) + f64x2_extract_lane::<1>(acc);
+
+            while i < len {
+                d += BUFFER_A.get(i) * BUFFER_B.get(i);
+                i += 1;
+            }
+            d
+        }
+    }
+
+    #[cfg(not(target_feature = "simd128"))]
+    unsafe {
+        dot4(BUFFER_A.as_ptr(), BUFFER_B.as_ptr(), len)
+    }
+}
+
+// 4-accumulator dot product of len f64s at a and b, shared by dot_simd's
+// fallback and the row-wise kernels.
+#[inline]
+unsafe fn dot4(a: *const f64, b: *const f64, len: usize) -> f64 {
+    let mut d0 = 0.0;
+    let mut d1 = 0.0;
+    let mut d2 = 0.0;
+    let mut d3 = 0.0;
+
+    let mut i = 0;
+    while i + 3 < len {
+        d0 += *a.add(i) * *b.add(i);
+        d1 += *a.add(i + 1) * *b.add(i + 1);
+        d2 += *a.add(i + 2) * *b.add(i + 2);
+        d3 += *a.add(i + 3) * *b.add(i + 3);
+        i += 4;
+    }
+    while i < len {
+        d0 += *a.add(i) * *b.add(i);
+        i += 1;
+    }
+    d0 + d1 + d2 + d3
 }
 
+// Smallest element of BUFFER_A. NaN-propagating: if any element is NaN the
+// result is NaN. Returns +inf for len == 0.
 #[no_mangle]
-pub extern "C" fn get_buffer_b_offset() -> u32 {
-    addr_of!(BUFFER_B) as u32
+pub extern "C" fn min(len: u32) -> f64 {
+    let len = clamp_len(len);
+    let mut m = f64::INFINITY;
+    unsafe {
+        for i in 0..len {
+            let x = BUFFER_A.get(i);
+            if x.is_nan() {
+                return x;
+            }
+            if x < m {
+                m = x;
+            }
+        }
+    }
+    m
 }
 
+// Largest element of BUFFER_A. NaN-propagating like min. Returns -inf for
+// len == 0.
 #[no_mangle]
-pub extern "C" fn get_result_offset() -> u32 {
-    addr_of!(RESULT) as u32
+pub extern "C" fn max(len: u32) -> f64 {
+    let len = clamp_len(len);
+    let mut m = f64::NEG_INFINITY;
+    unsafe {
+        for i in 0..len {
+            let x = BUFFER_A.get(i);
+            if x.is_nan() {
+                return x;
+            }
+            if x > m {
+                m = x;
+            }
+        }
+    }
+    m
 }
 
+// Index of the first minimum in BUFFER_A (ties go to the lowest index).
+// Consistent with min, the first NaN wins if there is one. Returns u32::MAX
+// for len == 0.
 #[no_mangle]
-pub extern "C" fn get_capacity() -> u32 {
-    CAPACITY as u32
+pub extern "C" fn argmin(len: u32) -> u32 {
+    let len = clamp_len(len);
+    if len == 0 {
+        return u32::MAX;
+    }
+    let mut idx = 0;
+    unsafe {
+        let mut m = BUFFER_A.get(0);
+        for i in 0..len {
+            let x = BUFFER_A.get(i);
+            if x.is_nan() {
+                return i as u32;
+            }
+            if x < m {
+                m = x;
+                idx = i;
+            }
+        }
+    }
+    idx as u32
+}
+
+// Index of the first maximum in BUFFER_A. Same tie and NaN rules as argmin.
+#[no_mangle]
+pub extern "C" fn argmax(len: u32) -> u32 {
+    let len = clamp_len(len);
+    if len == 0 {
+        return u32::MAX;
+    }
+    let mut idx = 0;
+    unsafe {
+        let mut m = BUFFER_A.get(0);
+        for i in 0..len {
+            let x = BUFFER_A.get(i);
+            if x.is_nan() {
+                return i as u32;
+            }
+            if x > m {
+                m = x;
+                idx = i;
+            }
+        }
+    }
+    idx as u32
+}
+
+// Welford's single-pass mean and sum of squared deviations (M2) over
+// BUFFER_A. Avoids the cancellation of "mean of squares minus square of
+// mean" on large-magnitude data.
+fn welford(len: usize) -> (f64, f64) {
+    welford_range(0, len)
+}
+
+// welford over BUFFER_A[start..end]
+fn welford_range(start: usize, end: usize) -> (f64, f64) {
+    let mut mean = 0.0;
+    let mut m2 = 0.0;
+    unsafe {
+        for i in start..end {
+            let x = BUFFER_A.get(i);
+            let delta = x - mean;
+            mean += delta / (i - start + 1) as f64;
+            m2 += delta * (x - mean);
+        }
+    }
+    (mean, m2)
+}
+
+// Arithmetic mean of BUFFER_A. NaN for len == 0.
+#[no_mangle]
+pub extern "C" fn mean(len: u32) -> f64 {
+    let len = clamp_len(len);
+    if len == 0 {
+        return f64::NAN;
+    }
+    sum(len as u32) / len as f64
+}
+
+// Population variance (divides by N). NaN for len == 0.
+#[no_mangle]
+pub extern "C" fn variance(len: u32) -> f64 {
+    let len = clamp_len(len);
+    if len == 0 {
+        return f64::NAN;
+    }
+    let (_, m2) = welford(len);
+    m2 / len as f64
+}
+
+// Sample variance (divides by N-1). NaN for len < 2.
+#[no_mangle]
+pub extern "C" fn variance_sample(len: u32) -> f64 {
+    let len = clamp_len(len);
+    if len < 2 {
+        return f64::NAN;
+    }
+    let (_, m2) = welford(len);
+    m2 / (len - 1) as f64
+}
+
+// Population standard deviation. NaN for len == 0.
+#[no_mangle]
+pub extern "C" fn stddev(len: u32) -> f64 {
+    libm::sqrt(variance(len))
+}
+
+// Mean into RESULT[0] and population stddev into RESULT[1] from one Welford
+// pass over BUFFER_A. Both NaN for len == 0.
+#[no_mangle]
+pub extern "C" fn mean_std(len: u32) {
+    let len = clamp_len(len);
+    let (mean, sd) = if len == 0 {
+        (f64::NAN, f64::NAN)
+    } else {
+        let (mean, m2) = welford(len);
+        (mean, libm::sqrt(m2 / len as f64))
+    };
+    unsafe {
+        RESULT.set(0, mean);
+        RESULT.set(1, sd);
+    }
+}
+
+// describe() in one pass over BUFFER_A, written to RESULT[0..5] as [count,
+// min, max, mean, population variance]. Welford for mean/variance, with min
+// and max tracked alongside. Matches the separate exports: min/max are
+// +inf/-inf and mean/variance NaN for len == 0, and any NaN makes all four
+// statistics NaN.
+#[no_mangle]
+pub extern "C" fn summary(len: u32) {
+    let len = clamp_len(len);
+    let mut lo = f64::INFINITY;
+    let mut hi = f64::NEG_INFINITY;
+    let mut mean = 0.0;
+    let mut m2 = 0.0;
+    unsafe {
+        for i in 0..len {
+            let x = BUFFER_A.get(i);
+            if x.is_nan() || x < lo {
+                lo = x;
+            }
+            if x.is_nan() || x > hi {
+                hi = x;
+            }
+            let delta = x - mean;
+            mean += delta / (i + 1) as f64;
+            m2 += delta * (x - mean);
+        }
+        let (mean, var) = if len == 0 {
+            (f64::NAN, f64::NAN)
+        } else {
+            (mean, m2 / len as f64)
+        };
+        RESULT.set(0, len as f64);
+        RESULT.set(1, lo);
+        RESULT.set(2, hi);
+        RESULT.set(3, mean);
+        RESULT.set(4, var);
+    }
+}
+
+// Sum of squares of BUFFER_A with the same 4-way unrolling as the sum_simd
+// fallback, so it auto-vectorizes.
+fn sum_squares(len: usize) -> f64 {
+    let mut s0 = 0.0;
+    let mut s1 = 0.0;
+    let mut s2 = 0.0;
+    let mut s3 = 0.0;
+
+    unsafe {
+        let mut i = 0;
+        while i + 3 < len {
+            let (x0, x1, x2, x3) = (
+                BUFFER_A.get(i),
+                BUFFER_A.get(i + 1),
+                BUFFER_A.get(i + 2),
+                BUFFER_A.get(i + 3),
+            );
+            s0 += x0 * x0;
+            s1 += x1 * x1;
+            s2 += x2 * x2;
+            s3 += x3 * x3;
+            i += 4;
+        }
+        while i < len {
+            let x = BUFFER_A.get(i);
+            s0 += x * x;
+            i += 1;
+        }
+    }
+    s0 + s1 + s2 + s3
+}
+
+// L2 norm of BUFFER_A: sqrt(sum of squares).
+#[no_mangle]
+pub extern "C" fn norm(len: u32) -> f64 {
+    let len = clamp_len(len);
+    libm::sqrt(sum_squares(len))
+}
+
+// BLAS asum: the L1 norm sum(|A[i]|), 4-way unrolled like sum_squares.
+#[no_mangle]
+pub extern "C" fn asum(len: u32) -> f64 {
+    let len = clamp_len(len);
+    let mut s0 = 0.0;
+    let mut s1 = 0.0;
+    let mut s2 = 0.0;
+    let mut s3 = 0.0;
+
+    unsafe {
+        let mut i = 0;
+        while i + 3 < len {
+            s0 += BUFFER_A.get(i).abs();
+            s1 += BUFFER_A.get(i + 1).abs();
+            s2 += BUFFER_A.get(i + 2).abs();
+            s3 += BUFFER_A.get(i + 3).abs();
+            i += 4;
+        }
+        while i < len {
+            s0 += BUFFER_A.get(i).abs();
+            i += 1;
+        }
+    }
+    s0 + s1 + s2 + s3
+}
+
+// sum(A[i]^2): norm without the sqrt.
+#[no_mangle]
+pub extern "C" fn sumsq(len: u32) -> f64 {
+    sum_squares(clamp_len(len))
+}
+
+// BLAS-style dot(A, A). Goes through sum_squares, which loads each element
+// once and squares it, instead of dot's two loads per element from what the
+// compiler can't prove is the same buffer. Same value as sumsq.
+#[no_mangle]
+pub extern "C" fn self_dot(len: u32) -> f64 {
+    sum_squares(clamp_len(len))
+}
+
+// Divide BUFFER_A in place by its L2 norm. A zero vector (norm == 0) is
+// left untouched rather than filled with NaN.
+#[no_mangle]
+pub extern "C" fn normalize(len: u32) {
+    let len = clamp_len(len);
+    let n = libm::sqrt(sum_squares(len));
+    if n == 0.0 {
+        return;
+    }
+    unsafe {
+        for i in 0..len {
+            BUFFER_A.set(i, BUFFER_A.get(i) / n);
+        }
+    }
+}
+
+// normalize applied to each row of BUFFER_A (rows x cols, row-major): every
+// row is divided by its own L2 norm in place, and zero-norm rows are left
+// untouched. Does nothing if rows * cols exceeds CAPACITY.
+#[no_mangle]
+pub extern "C" fn normalize_rows(rows: u32, cols: u32) {
+    if matrix_len(rows, cols).is_none() {
+        return;
+    }
+    let (rows, cols) = (rows as usize, cols as usize);
+    for r in 0..rows {
+        let row = unsafe { &mut BUFFER_A.as_mut_slice((r + 1) * cols)[r * cols..] };
+        let n = libm::sqrt(row.iter().map(|x| x * x).sum::<f64>());
+        if n != 0.0 {
+            for x in row.iter_mut() {
+                *x /= n;
+            }
+        }
+    }
+}
+
+// Z-score BUFFER_A in place: (x - mean) / stddev, using the population
+// stddev from one Welford pass. If the stddev is zero (constant input, or
+// len == 1) the centered values - all zero - are kept instead of dividing.
+#[no_mangle]
+pub extern "C" fn standardize(len: u32) {
+    let len = clamp_len(len);
+    if len == 0 {
+        return;
+    }
+    let (mean, m2) = welford(len);
+    let sd = libm::sqrt(m2 / len as f64);
+    let sd = if sd == 0.0 { 1.0 } else { sd };
+    unsafe {
+        for i in 0..len {
+            BUFFER_A.set(i, (BUFFER_A.get(i) - mean) / sd);
+        }
+    }
+}
+
+// Rescale BUFFER_A in place so its minimum maps to lo and its maximum to hi:
+// lo + (x - min) / (max - min) * (hi - lo). The extremes are found in one
+// pass, ignoring NaN (which stays NaN). If there is no spread (max == min,
+// or nothing but NaN) every non-NaN element is set to lo rather than
+// dividing by zero.
+#[no_mangle]
+pub extern "C" fn minmax_normalize_to(lo: f64, hi: f64, len: u32) {
+    let len = clamp_len(len);
+    let mut min = f64::INFINITY;
+    let mut max = f64::NEG_INFINITY;
+    unsafe {
+        for i in 0..len {
+            let x = BUFFER_A.get(i);
+            min = min.min(x);
+            max = max.max(x);
+        }
+        if max <= min {
+            for i in 0..len {
+                if !BUFFER_A.get(i).is_nan() {
+                    BUFFER_A.set(i, lo);
+                }
+            }
+            return;
+        }
+        let range = max - min;
+        for i in 0..len {
+            let t = (BUFFER_A.get(i) - min) / range;
+            BUFFER_A.set(i, mul_add(t, hi - lo, lo));
+        }
+    }
+}
+
+// minmax_normalize_to(0.0, 1.0, len). A constant vector becomes all 0.0.
+#[no_mangle]
+pub extern "C" fn minmax_normalize(len: u32) {
+    minmax_normalize_to(0.0, 1.0, len);
+}
+
+// L1-normalize BUFFER_A in place so it sums to 1.0 (divide by the plain
+// sum, so meant for non-negative weights). If the sum is zero the buffer is
+// left unchanged and ERR_ZERO_DIVISOR is set.
+#[no_mangle]
+pub extern "C" fn normalize_sum(len: u32) {
+    let len = clamp_len(len);
+    let s = sum(len as u32);
+    if s == 0.0 {
+        set_error(ERR_ZERO_DIVISOR);
+        return;
+    }
+    unsafe {
+        for i in 0..len {
+            BUFFER_A.set(i, BUFFER_A.get(i) / s);
+        }
+    }
+}
+
+// Cosine similarity of BUFFER_A and BUFFER_B. The dot product and both
+// squared norms are accumulated in one sweep. Returns 0.0 if either vector
+// has zero norm.
+#[no_mangle]
+pub extern "C" fn cosine(len: u32) -> f64 {
+    let len = clamp_len(len);
+    let mut d = 0.0;
+    let mut aa = 0.0;
+    let mut bb = 0.0;
+    unsafe {
+        for i in 0..len {
+            let x = BUFFER_A.get(i);
+            let y = BUFFER_B.get(i);
+            d += x * y;
+            aa += x * x;
+            bb += y * y;
+        }
+    }
+    if aa == 0.0 || bb == 0.0 {
+        return 0.0;
+    }
+    d / (libm::sqrt(aa) * libm::sqrt(bb))
+}
+
+// Squared Euclidean distance between BUFFER_A and BUFFER_B, 4-way unrolled.
+#[no_mangle]
+pub extern "C" fn euclidean_sq(len: u32) -> f64 {
+    let len = clamp_len(len);
+    let mut s0 = 0.0;
+    let mut s1 = 0.0;
+    let mut s2 = 0.0;
+    let mut s3 = 0.0;
+
+    unsafe {
+        let mut i = 0;
+        while i + 3 < len {
+            let d0 = BUFFER_A.get(i) - BUFFER_B.get(i);
+            let d1 = BUFFER_A.get(i + 1) - BUFFER_B.get(i + 1);
+            let d2 = BUFFER_A.get(i + 2) - BUFFER_B.get(i + 2);
+            let d3 = BUFFER_A.get(i + 3) - BUFFER_B.get(i + 3);
+            s0 += d0 * d0;
+            s1 += d1 * d1;
+            s2 += d2 * d2;
+            s3 += d3 * d3;
+            i += 4;
+        }
+        while i < len {
+            let d = BUFFER_A.get(i) - BUFFER_B.get(i);
+            s0 += d * d;
+            i += 1;
+        }
+    }
+    s0 + s1 + s2 + s3
+}
+
+// euclidean_sq with early exit for nearest-neighbour pruning: the running
+// sum is checked against bound after every 4 elements, and once it exceeds
+// bound the scan stops and returns f64::INFINITY. That is only a signal that
+// the distance is > bound, not the distance itself. Otherwise returns the
+// same value as euclidean_sq.
+#[no_mangle]
+pub extern "C" fn euclidean_sq_bounded(bound: f64, len: u32) -> f64 {
+    let len = clamp_len(len);
+    let mut s0 = 0.0;
+    let mut s1 = 0.0;
+    let mut s2 = 0.0;
+    let mut s3 = 0.0;
+
+    unsafe {
+        let mut i = 0;
+        while i + 3 < len {
+            let d0 = BUFFER_A.get(i) - BUFFER_B.get(i);
+            let d1 = BUFFER_A.get(i + 1) - BUFFER_B.get(i + 1);
+            let d2 = BUFFER_A.get(i + 2) - BUFFER_B.get(i + 2);
+            let d3 = BUFFER_A.get(i + 3) - BUFFER_B.get(i + 3);
+            s0 += d0 * d0;
+            s1 += d1 * d1;
+            s2 += d2 * d2;
+            s3 += d3 * d3;
+            if (s0 + s1) + (s2 + s3) > bound {
+                return f64::INFINITY;
+            }
+            i += 4;
+        }
+        while i < len {
+            let d = BUFFER_A.get(i) - BUFFER_B.get(i);
+            s0 += d * d;
+            i += 1;
+        }
+    }
+    let s = s0 + s1 + s2 + s3;
+    if s > bound {
+        f64::INFINITY
+    } else {
+        s
+    }
+}
+
+// Euclidean distance between BUFFER_A and BUFFER_B. For nearest-neighbor
+// ranking prefer euclidean_sq, which preserves ordering and skips the sqrt.
+#[no_mangle]
+pub extern "C" fn euclidean(len: u32) -> f64 {
+    libm::sqrt(euclidean_sq(len))
+}
+
+// Manhattan (L1) distance between BUFFER_A and BUFFER_B, 4-way unrolled.
+#[no_mangle]
+pub extern "C" fn manhattan(len: u32) -> f64 {
+    let len = clamp_len(len);
+    let mut s0 = 0.0;
+    let mut s1 = 0.0;
+    let mut s2 = 0.0;
+    let mut s3 = 0.0;
+
+    unsafe {
+        let mut i = 0;
+        while i + 3 < len {
+            s0 += (BUFFER_A.get(i) - BUFFER_B.get(i)).abs();
+            s1 += (BUFFER_A.get(i + 1) - BUFFER_B.get(i + 1)).abs();
+            s2 += (BUFFER_A.get(i + 2) - BUFFER_B.get(i + 2)).abs();
+            s3 += (BUFFER_A.get(i + 3) - BUFFER_B.get(i + 3)).abs();
+            i += 4;
+        }
+        while i < len {
+            s0 += (BUFFER_A.get(i) - BUFFER_B.get(i)).abs();
+            i += 1;
+        }
+    }
+    s0 + s1 + s2 + s3
+}
+
+// numpy-style allclose: 1 if |A[i] - B[i]| <= atol + rtol * |B[i]| for every
+// i in 0..len, else 0. Stops at the first mismatch. Equal infinities are
+// close; a NaN on either side never is. Empty input is 1.
+#[no_mangle]
+pub extern "C" fn allclose(rtol: f64, atol: f64, len: u32) -> u32 {
+    let len = clamp_len(len);
+    unsafe {
+        for i in 0..len {
+            let (a, b) = (BUFFER_A.get(i), BUFFER_B.get(i));
+            let close = a == b || (a - b).abs() <= atol + rtol * b.abs();
+            if !close {
+                return 0;
+            }
+        }
+    }
+    1
+}
+
+// Largest |A[i] - B[i]|. NaN if any difference is NaN, 0.0 for len == 0.
+#[no_mangle]
+pub extern "C" fn max_abs_diff(len: u32) -> f64 {
+    let len = clamp_len(len);
+    let mut m: f64 = 0.0;
+    unsafe {
+        for i in 0..len {
+            let d = (BUFFER_A.get(i) - BUFFER_B.get(i)).abs();
+            if d.is_nan() {
+                return f64::NAN;
+            }
+            m = m.max(d);
+        }
+    }
+    m
+}
+
+// Fraction of i in 0..len where A[i] and B[i] have the same sign: both
+// positive, both negative, or both zero (-0.0 counts as zero, not
+// negative). A NaN on either side never agrees. NaN for len == 0.
+#[no_mangle]
+pub extern "C" fn sign_agreement(len: u32) -> f64 {
+    let len = clamp_len(len);
+    if len == 0 {
+        return f64::NAN;
+    }
+    let mut n = 0u32;
+    unsafe {
+        for i in 0..len {
+            let (a, b) = (BUFFER_A.get(i), BUFFER_B.get(i));
+            n += ((a > 0.0 && b > 0.0) || (a < 0.0 && b < 0.0) || (a == 0.0 && b == 0.0)) as u32;
+        }
+    }
+    n as f64 / len as f64
+}
+
+// In-place inclusive prefix sum: BUFFER_A[i] = sum(BUFFER_A[0..=i]).
+// O(n), with a single running accumulator. Each output depends on the
+// previous one, so this is a sequential scan - it neither vectorizes like
+// sum_simd nor splits safely across threads in place.
+#[no_mangle]
+pub extern "C" fn prefix_sum(len: u32) {
+    let len = clamp_len(len);
+    let mut acc = 0.0;
+    unsafe {
+        for i in 0..len {
+            acc += BUFFER_A.get(i);
+            BUFFER_A.set(i, acc);
+        }
+    }
+}
+
+// Same as prefix_sum, but writes into RESULT and leaves BUFFER_A intact.
+#[no_mangle]
+pub extern "C" fn prefix_sum_to_result(len: u32) {
+    let len = clamp_len(len);
+    let mut acc = 0.0;
+    unsafe {
+        for i in 0..len {
+            acc += BUFFER_A.get(i);
+            RESULT.set(i, acc);
+        }
+    }
+}
+
+// In-place inclusive running product: BUFFER_A[i] = product(A[0..=i]).
+// Large products overflow to +/-inf, and a zero anywhere zeroes every later
+// entry (or makes it NaN if an infinity follows, since 0 * inf = NaN).
+#[no_mangle]
+pub extern "C" fn prefix_product(len: u32) {
+    let len = clamp_len(len);
+    let mut acc = 1.0;
+    unsafe {
+        for i in 0..len {
+            acc *= BUFFER_A.get(i);
+            BUFFER_A.set(i, acc);
+        }
+    }
+}
+
+// Same as prefix_product, but writes into RESULT and leaves BUFFER_A intact.
+#[no_mangle]
+pub extern "C" fn prefix_product_to_result(len: u32) {
+    let len = clamp_len(len);
+    let mut acc = 1.0;
+    unsafe {
+        for i in 0..len {
+            acc *= BUFFER_A.get(i);
+            RESULT.set(i, acc);
+        }
+    }
+}
+
+// dst[i] = extreme of BUFFER_A[0..=i], where better(x, acc) says x should
+// replace the running value. NaN poisons: once seen, every later entry is
+// NaN, matching the NaN-propagating min and max.
+fn running_extreme(dst: &StaticBuffer<f64>, better: fn(f64, f64) -> bool, len: usize) {
+    let mut acc = f64::NAN;
+    unsafe {
+        for i in 0..len {
+            let x = BUFFER_A.get(i);
+            if i == 0 || x.is_nan() || better(x, acc) {
+                acc = x;
+            }
+            dst.set(i, acc);
+        }
+    }
+}
+
+// Cumulative maximum of BUFFER_A, in place. NaN poisons later entries.
+#[no_mangle]
+pub extern "C" fn running_max(len: u32) {
+    let len = clamp_len(len);
+    running_extreme(&BUFFER_A, |x, m| x > m, len);
+}
+
+// Cumulative minimum of BUFFER_A, in place. NaN poisons later entries.
+#[no_mangle]
+pub extern "C" fn running_min(len: u32) {
+    let len = clamp_len(len);
+    running_extreme(&BUFFER_A, |x, m| x < m, len);
+}
+
+#[no_mangle]
+pub extern "C" fn running_max_to_result(len: u32) {
+    let len = clamp_len(len);
+    running_extreme(&RESULT, |x, m| x > m, len);
+}
+
+#[no_mangle]
+pub extern "C" fn running_min_to_result(len: u32) {
+    let len = clamp_len(len);
+    running_extreme(&RESULT, |x, m| x < m, len);
+}
+
+// max(x, lo) then min(.., hi) with explicit comparisons: NaN fails both and
+// passes through unchanged, and lo > hi pins every number to hi.
+#[inline]
+fn clamp_value(x: f64, lo: f64, hi: f64) -> f64 {
+    let x = if x < lo { lo } else { x };
+    if x > hi {
+        hi
+    } else {
+        x
+    }
+}
+
+// Clamp BUFFER_A in place into [lo, hi]. See clamp_value for NaN and
+// lo > hi behavior.
+#[no_mangle]
+pub extern "C" fn clamp(lo: f64, hi: f64, len: u32) {
+    let len = clamp_len(len);
+    unsafe {
+        for i in 0..len {
+            BUFFER_A.set(i, clamp_value(BUFFER_A.get(i), lo, hi));
+        }
+    }
+}
+
+// Same as clamp, but writes into RESULT and leaves BUFFER_A intact.
+#[no_mangle]
+pub extern "C" fn clamp_to_result(lo: f64, hi: f64, len: u32) {
+    let len = clamp_len(len);
+    unsafe {
+        for i in 0..len {
+            RESULT.set(i, clamp_value(BUFFER_A.get(i), lo, hi));
+        }
+    }
+}
+
+// clamp, returning how many elements actually changed (were outside
+// [lo, hi]). NaN stays NaN and is not counted.
+#[no_mangle]
+pub extern "C" fn clamp_count(lo: f64, hi: f64, len: u32) -> u32 {
+    let len = clamp_len(len);
+    let mut n = 0;
+    unsafe {
+        for i in 0..len {
+            let x = BUFFER_A.get(i);
+            let y = clamp_value(x, lo, hi);
+            if y != x && !x.is_nan() {
+                BUFFER_A.set(i, y);
+                n += 1;
+            }
+        }
+    }
+    n
+}
+
+// BUFFER_A[i] = clamp(a * x + b, lo, hi) in place, with a * x + b as one
+// mul_add. NaN (in x, or produced by a * x + b) passes through; lo > hi pins
+// to hi, as in clamp.
+#[no_mangle]
+pub extern "C" fn affine_clamp(a: f64, b: f64, lo: f64, hi: f64, len: u32) {
+    let len = clamp_len(len);
+    unsafe {
+        for i in 0..len {
+            BUFFER_A.set(i, clamp_value(mul_add(a, BUFFER_A.get(i), b), lo, hi));
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn fill_a(value: f64, len: u32) {
+    let len = clamp_len(len);
+    unsafe { BUFFER_A.fill(len, value) }
+}
+
+#[no_mangle]
+pub extern "C" fn fill_b(value: f64, len: u32) {
+    let len = clamp_len(len);
+    unsafe { BUFFER_B.fill(len, value) }
+}
+
+#[no_mangle]
+pub extern "C" fn fill_result(value: f64, len: u32) {
+    let len = clamp_len(len);
+    unsafe { RESULT.fill(len, value) }
+}
+
+// Zero the whole of BUFFER_A, BUFFER_B and RESULT (all CAPACITY elements).
+#[no_mangle]
+pub extern "C" fn clear_all() {
+    unsafe {
+        BUFFER_A.fill(CAPACITY, 0.0);
+        BUFFER_B.fill(CAPACITY, 0.0);
+        RESULT.fill(CAPACITY, 0.0);
+    }
+}
+
+// Fill the first byte_len bytes of BUFFER_A with the low 8 bits of
+// byte_value. byte_len is clamped to get_buffer_a_byte_len().
+#[no_mangle]
+pub extern "C" fn memset_a(byte_value: u32, byte_len: u32) {
+    let mut byte_len = byte_len as usize;
+    if byte_len > CAPACITY * 8 {
+        set_error(ERR_LEN_CLAMPED);
+        byte_len = CAPACITY * 8;
+    }
+    unsafe { core::ptr::write_bytes(BUFFER_A.as_mut_ptr() as *mut u8, byte_value as u8, byte_len) }
+}
+
+#[no_mangle]
+pub extern "C" fn copy_a_to_b(len: u32) {
+    let len = clamp_len(len);
+    unsafe { BUFFER_B.copy_from(&BUFFER_A, len) }
+}
+
+#[no_mangle]
+pub extern "C" fn copy_b_to_a(len: u32) {
+    let len = clamp_len(len);
+    unsafe { BUFFER_A.copy_from(&BUFFER_B, len) }
+}
+
+#[no_mangle]
+pub extern "C" fn copy_a_to_result(len: u32) {
+    let len = clamp_len(len);
+    unsafe { RESULT.copy_from(&BUFFER_A, len) }
+}
+
+// Exchange the first len elements of BUFFER_A and BUFFER_B, one scalar
+// temporary at a time.
+#[no_mangle]
+pub extern "C" fn swap_ab(len: u32) {
+    let len = clamp_len(len);
+    unsafe {
+        for i in 0..len {
+            let t = BUFFER_A.get(i);
+            BUFFER_A.set(i, BUFFER_B.get(i));
+            BUFFER_B.set(i, t);
+        }
+    }
+}
+
+// Reverse BUFFER_A[0..len] in place, swapping from both ends inward.
+#[no_mangle]
+pub extern "C" fn reverse_a(len: u32) {
+    let len = clamp_len(len);
+    unsafe { BUFFER_A.as_mut_slice(len).reverse() }
+}
+
+// RESULT[i] = BUFFER_A[len - 1 - i], leaving BUFFER_A untouched.
+#[no_mangle]
+pub extern "C" fn reverse_a_to_result(len: u32) {
+    let len = clamp_len(len);
+    unsafe {
+        for i in 0..len {
+            RESULT.set(i, BUFFER_A.get(len - 1 - i));
+        }
+    }
+}
+
+// Rotate BUFFER_A[0..len] left by shift (mod len) in place with three
+// reversals: reverse [0..shift], reverse [shift..len], reverse all. No
+// scratch space. shift == len / 2 is fftshift for even len.
+#[no_mangle]
+pub extern "C" fn rotate_a(shift: u32, len: u32) {
+    let len = clamp_len(len);
+    if len == 0 {
+        return;
+    }
+    let shift = shift as usize % len;
+    let a = unsafe { BUFFER_A.as_mut_slice(len) };
+    a[..shift].reverse();
+    a[shift..].reverse();
+    a.reverse();
+}
+
+// BLAS axpy: BUFFER_B[i] = a * BUFFER_A[i] + BUFFER_B[i], in place over B.
+#[no_mangle]
+pub extern "C" fn axpy(a: f64, len: u32) {
+    let len = clamp_len(len);
+    unsafe {
+        for i in 0..len {
+            BUFFER_B.set(i, mul_add(a, BUFFER_A.get(i), BUFFER_B.get(i)));
+        }
+    }
+}
+
+// Linear interpolation: RESULT[i] = A[i] + t * (B[i] - A[i]). t outside
+// [0, 1] extrapolates.
+#[no_mangle]
+pub extern "C" fn lerp(t: f64, len: u32) {
+    let len = clamp_len(len);
+    unsafe {
+        for i in 0..len {
+            let a = BUFFER_A.get(i);
+            RESULT.set(i, mul_add(t, BUFFER_B.get(i) - a, a));
+        }
+    }
+}
+
+// RESULT[i] += BUFFER_A[i] * BUFFER_B[i]. Accumulates into whatever RESULT
+// already holds, so a sequence of calls sums products across batches;
+// zero RESULT first with fill_result(0.0, len) or clear_all.
+#[no_mangle]
+pub extern "C" fn fma_accumulate(len: u32) {
+    let len = clamp_len(len);
+    unsafe {
+        for i in 0..len {
+            RESULT.set(i, mul_add(BUFFER_A.get(i), BUFFER_B.get(i), RESULT.get(i)));
+        }
+    }
+}
+
+// D = A * B + C with RESULT as both the addend C and the output D:
+// RESULT[i] = mul_add(A[i], B[i], RESULT[i]). The same operation as
+// fma_accumulate, under the name for code that thinks of RESULT as the third
+// operand. As noted at mul_add, the fused step guarantees one rounding but
+// is not a hardware instruction on wasm32.
+#[no_mangle]
+pub extern "C" fn fma_into_result(len: u32) {
+    fma_accumulate(len);
+}
+
+// Fold BUFFER_A into a running average held in RESULT: RESULT[i] = alpha *
+// A[i] + (1 - alpha) * RESULT[i], evaluated as RESULT[i] + alpha * (A[i] -
+// RESULT[i]). Seed RESULT with the first sample (copy_a_to_result), then
+// load each new sample into A and call this.
+#[no_mangle]
+pub extern "C" fn ema_vector(alpha: f64, len: u32) {
+    let len = clamp_len(len);
+    unsafe {
+        for i in 0..len {
+            let r = RESULT.get(i);
+            RESULT.set(i, mul_add(alpha, BUFFER_A.get(i) - r, r));
+        }
+    }
+}
+
+// RESULT[i] = scalar * BUFFER_A[i] * BUFFER_B[i] in one pass, evaluated
+// left to right as (scalar * A[i]) * B[i].
+#[no_mangle]
+pub extern "C" fn scaled_mul(scalar: f64, len: u32) {
+    let len = clamp_len(len);
+    unsafe {
+        for i in 0..len {
+            RESULT.set(i, scalar * BUFFER_A.get(i) * BUFFER_B.get(i));
+        }
+    }
+}
+
+// Element-wise transcendental transforms of BUFFER_A, in place, via libm
+// (a port of musl): sqrt is correctly rounded, exp and ln are within 1 ulp.
+// Negative inputs give NaN for sqrt and ln (except sqrt(-0.0) = -0.0), and
+// ln(0.0) = -inf.
+#[no_mangle]
+pub extern "C" fn sqrt_a(len: u32) {
+    let len = clamp_len(len);
+    unsafe {
+        for i in 0..len {
+            BUFFER_A.set(i, libm::sqrt(BUFFER_A.get(i)));
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn exp_a(len: u32) {
+    let len = clamp_len(len);
+    unsafe {
+        for i in 0..len {
+            BUFFER_A.set(i, libm::exp(BUFFER_A.get(i)));
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn ln_a(len: u32) {
+    let len = clamp_len(len);
+    unsafe {
+        for i in 0..len {
+            BUFFER_A.set(i, libm::log(BUFFER_A.get(i)));
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn abs_a(len: u32) {
+    let len = clamp_len(len);
+    unsafe {
+        for i in 0..len {
+            BUFFER_A.set(i, BUFFER_A.get(i).abs());
+        }
+    }
+}
+
+// Replace each element of BUFFER_A with -1.0, 0.0 or +1.0. Zeros keep their
+// own sign (-0.0 stays -0.0) and NaN stays NaN, unlike f64::signum which
+// maps +/-0.0 to +/-1.0.
+#[no_mangle]
+pub extern "C" fn sign_a(len: u32) {
+    let len = clamp_len(len);
+    unsafe {
+        for i in 0..len {
+            let x = BUFFER_A.get(i);
+            let s = if x > 0.0 {
+                1.0
+            } else if x < 0.0 {
+                -1.0
+            } else {
+                x
+            };
+            BUFFER_A.set(i, s);
+        }
+    }
+}
+
+// In-place 1.0 / x. Zeros become +/-inf, matching div.
+#[no_mangle]
+pub extern "C" fn recip_a(len: u32) {
+    let len = clamp_len(len);
+    unsafe {
+        for i in 0..len {
+            BUFFER_A.set(i, 1.0 / BUFFER_A.get(i));
+        }
+    }
+}
+
+// Raise each element of BUFFER_A to a constant power in place. Exponents of
+// exactly 2.0 and 3.0 take a multiply-only fast path; everything else goes
+// through libm::pow. Negative bases with non-integer exponents give NaN.
+#[no_mangle]
+pub extern "C" fn pow_a(exponent: f64, len: u32) {
+    let len = clamp_len(len);
+    unsafe {
+        if exponent == 2.0 {
+            for i in 0..len {
+                let x = BUFFER_A.get(i);
+                BUFFER_A.set(i, x * x);
+            }
+        } else if exponent == 3.0 {
+            for i in 0..len {
+                let x = BUFFER_A.get(i);
+                BUFFER_A.set(i, x * x * x);
+            }
+        } else {
+            for i in 0..len {
+                BUFFER_A.set(i, libm::pow(BUFFER_A.get(i), exponent));
+            }
+        }
+    }
+}
+
+// BUFFER_A[i] *= 2^exp in place via libm::ldexp (scalbn), which adjusts the
+// exponent directly. Exact whenever the result is a normal number. Past the
+// top of the range it overflows to +/-inf; below it results go subnormal,
+// where low mantissa bits are rounded away (round-to-nearest), and then to
+// +/-0.0. inf, NaN and zeros pass through.
+#[no_mangle]
+pub extern "C" fn ldexp_a(exp: i32, len: u32) {
+    let len = clamp_len(len);
+    unsafe {
+        for i in 0..len {
+            BUFFER_A.set(i, libm::ldexp(BUFFER_A.get(i), exp));
+        }
+    }
+}
+
+// Number of elements start, start+stride, ... (up to len of them) that lie
+// inside a buffer. stride == 0 reads buffer[start] len times, with len
+// clamped to CAPACITY like any other length.
+fn strided_count(start: u32, stride: u32, len: u32) -> usize {
+    let (start, stride, len) = (start as usize, stride as usize, len as usize);
+    if start >= CAPACITY {
+        if len > 0 {
+            set_error(ERR_INDEX_RANGE);
+        }
+        return 0;
+    }
+    if stride == 0 {
+        return clamp_len(len as u32);
+    }
+    let fit = (CAPACITY - 1 - start) / stride + 1;
+    if len > fit {
+        set_error(ERR_LEN_CLAMPED);
+        return fit;
+    }
+    len
+}
+
+// Sum of BUFFER_A[start + i*stride] for i in 0..len, for picking one channel
+// out of interleaved data. The count is clamped so no read passes CAPACITY;
+// stride == 0 sums the same element len times.
+#[no_mangle]
+pub extern "C" fn sum_strided(start: u32, stride: u32, len: u32) -> f64 {
+    let n = strided_count(start, stride, len);
+    let (start, stride) = (start as usize, stride as usize);
+    let mut s = 0.0;
+    unsafe {
+        for i in 0..n {
+            s += BUFFER_A.get(start + i * stride);
+        }
+    }
+    s
+}
+
+// Dot product of BUFFER_A[start_a + i*stride_a] and BUFFER_B[start_b +
+// i*stride_b]. The count is clamped so neither side reads past CAPACITY.
+#[no_mangle]
+pub extern "C" fn dot_strided(
+    start_a: u32,
+    stride_a: u32,
+    start_b: u32,
+    stride_b: u32,
+    len: u32,
+) -> f64 {
+    let n = strided_count(start_a, stride_a, len).min(strided_count(start_b, stride_b, len));
+    let (start_a, stride_a) = (start_a as usize, stride_a as usize);
+    let (start_b, stride_b) = (start_b as usize, stride_b as usize);
+    let mut d = 0.0;
+    unsafe {
+        for i in 0..n {
+            d += BUFFER_A.get(start_a + i * stride_a) * BUFFER_B.get(start_b + i * stride_b);
+        }
+    }
+    d
+}
+
+// Per-channel sum of squares of interleaved BUFFER_A (frames x num_channels,
+// frame-major): RESULT[c] = sum over f of A[f * num_channels + c]^2, in one
+// pass. Does nothing if num_channels * frames or num_channels exceeds
+// CAPACITY.
+#[no_mangle]
+pub extern "C" fn channel_energy(num_channels: u32, frames: u32) {
+    if matrix_len(frames, num_channels).is_none() || matrix_len(num_channels, 1).is_none() {
+        return;
+    }
+    let (ch, frames) = (num_channels as usize, frames as usize);
+    unsafe {
+        RESULT.fill(ch, 0.0);
+        for f in 0..frames {
+            for c in 0..ch {
+                let x = BUFFER_A.get(f * ch + c);
+                RESULT.set(c, RESULT.get(c) + x * x);
+            }
+        }
+    }
+}
+
+// Clamp [start, start+len) to the buffer, returning (start, end). A start at
+// or past CAPACITY yields an empty range and ERR_INDEX_RANGE.
+fn clamp_range(start: u32, len: u32) -> (usize, usize) {
+    let (start, len) = (start as usize, len as usize);
+    if start >= CAPACITY {
+        set_error(ERR_INDEX_RANGE);
+        return (CAPACITY, CAPACITY);
+    }
+    if len > CAPACITY - start {
+        set_error(ERR_LEN_CLAMPED);
+        return (start, CAPACITY);
+    }
+    (start, start + len)
+}
+
+// Sum of BUFFER_A[start..start+len], clamped to CAPACITY.
+#[no_mangle]
+pub extern "C" fn sum_range(start: u32, len: u32) -> f64 {
+    let (start, end) = clamp_range(start, len);
+    let mut s = 0.0;
+    unsafe {
+        for i in start..end {
+            s += BUFFER_A.get(i);
+        }
+    }
+    s
+}
+
+// Scale BUFFER_A[start..start+len] in place, clamped to CAPACITY.
+#[no_mangle]
+pub extern "C" fn scale_range(scalar: f64, start: u32, len: u32) {
+    let (start, end) = clamp_range(start, len);
+    unsafe {
+        for i in start..end {
+            BUFFER_A.set(i, BUFFER_A.get(i) * scalar);
+        }
+    }
+}
+
+// Dot product of BUFFER_A[start..start+len] and BUFFER_B over the same
+// window, clamped to CAPACITY. Uses the 4-accumulator kernel.
+#[no_mangle]
+pub extern "C" fn dot_range(start: u32, len: u32) -> f64 {
+    let (start, end) = clamp_range(start, len);
+    let (a, b) = (BUFFER_A.as_ptr(), BUFFER_B.as_ptr());
+    unsafe { dot4(a.add(start), b.add(start), end - start) }
+}
+
+// Slice reductions for hosts that partition work across threads sharing
+// this module's memory: each thread reduces its own [start, start+len) and
+// the host adds the partials. Same values as sum_range/dot_range. They only
+// read A and B, but StaticBuffer's Sync impl assumes a single thread, so it
+// is the caller's job to use non-overlapping ranges that cover the data
+// once, and to keep every writer of A and B out while they run. The error
+// register is not thread-aware: check it after joining.
+#[no_mangle]
+pub extern "C" fn sum_partial(start: u32, len: u32) -> f64 {
+    sum_range(start, len)
+}
+
+#[no_mangle]
+pub extern "C" fn dot_partial(start: u32, len: u32) -> f64 {
+    dot_range(start, len)
+}
+
+// Sort BUFFER_A[0..len] ascending in place, NaNs last (see cmp_nan_last).
+// Uses core's sort_unstable_by, an in-place introsort-style sort that needs
+// no allocator. Not stable, which is unobservable except for -0.0/0.0.
+#[no_mangle]
+pub extern "C" fn sort_a(len: u32) {
+    let len = clamp_len(len);
+    unsafe { BUFFER_A.as_mut_slice(len).sort_unstable_by(cmp_nan_last) }
+}
+
+// Binary search in sorted BUFFER_A[0..len]: index of the first element >=
+// target, or len if there is none (numpy searchsorted, side='left').
+// O(log len). With a normalized prefix sum in A and a uniform draw as
+// target, this is inverse-CDF (categorical) sampling.
+#[no_mangle]
+pub extern "C" fn searchsorted(target: f64, len: u32) -> u32 {
+    let len = clamp_len(len);
+    let (mut lo, mut hi) = (0, len);
+    unsafe {
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            if BUFFER_A.get(mid) < target {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+    }
+    lo as u32
+}
+
+// Write the k largest elements of BUFFER_A[0..len] into RESULT[0..k] in
+// descending order and return how many were written (min(k, len)). BUFFER_A
+// is untouched: RESULT[0..len] is used as scratch for an in-place
+// quickselect, so RESULT[k..len] holds the leftovers in no particular order.
+// NaNs rank below every number.
+#[no_mangle]
+pub extern "C" fn top_k(k: u32, len: u32) -> u32 {
+    let len = clamp_len(len);
+    let k = (k as usize).min(len);
+    if k == 0 {
+        return 0;
+    }
+    let desc = |a: &f64, b: &f64| match (a.is_nan(), b.is_nan()) {
+        (false, false) => cmp_nan_last(b, a),
+        _ => cmp_nan_last(a, b),
+    };
+    unsafe {
+        RESULT.copy_from(&BUFFER_A, len);
+        let scratch = RESULT.as_mut_slice(len);
+        if k < len {
+            scratch.select_nth_unstable_by(k - 1, desc);
+        }
+        scratch[..k].sort_unstable_by(desc);
+    }
+    k as u32
+}
+
+// Type-7 percentile (linear interpolation between the two nearest ranks) of
+// BUFFER_A[0..len], using RESULT[0..len] as quickselect scratch so BUFFER_A
+// is preserved. NaNs rank above every number, as in sort_a. Only the first
+// `ranked` positions of that order are ranked, so passing len minus the NaN
+// count takes the percentile over the numbers alone.
+fn percentile_of(p: f64, len: usize, ranked: usize) -> f64 {
+    if ranked == 0 || !(0.0..=100.0).contains(&p) {
+        return f64::NAN;
+    }
+    let h = (ranked - 1) as f64 * (p / 100.0);
+    let lo = libm::floor(h) as usize;
+    let frac = h - lo as f64;
+    unsafe {
+        RESULT.copy_from(&BUFFER_A, len);
+        let scratch = RESULT.as_mut_slice(len);
+        let (_, &mut v_lo, rest) = scratch.select_nth_unstable_by(lo, cmp_nan_last);
+        if frac == 0.0 || rest.is_empty() {
+            return v_lo;
+        }
+        let v_hi = rest.iter().copied().min_by(cmp_nan_last).unwrap_or(v_lo);
+        v_lo + frac * (v_hi - v_lo)
+    }
+}
+
+// Median of BUFFER_A; even lengths average the two middle elements. NaN for
+// len == 0. Clobbers RESULT[0..len], not BUFFER_A.
+#[no_mangle]
+pub extern "C" fn median(len: u32) -> f64 {
+    let len = clamp_len(len);
+    percentile_of(50.0, len, len)
+}
+
+// p-th percentile of BUFFER_A for p in [0, 100], interpolating linearly
+// between closest ranks (numpy's default, "type 7"). NaN for len == 0 or p
+// outside [0, 100]. Clobbers RESULT[0..len], not BUFFER_A.
+#[no_mangle]
+pub extern "C" fn percentile(p: f64, len: u32) -> f64 {
+    let len = clamp_len(len);
+    percentile_of(p, len, len)
+}
+
+// Winsorize BUFFER_A in place: clamp every element into [the lower_pct-th,
+// the upper_pct-th] percentile (type 7, as percentile). NaNs stay NaN and
+// are left out of both percentiles, which are taken over the numbers alone.
+// Requires 0 <= lower_pct < upper_pct <= 100; anything else does nothing
+// and sets ERR_INVALID_ARG. Clobbers RESULT[0..len].
+#[no_mangle]
+pub extern "C" fn winsorize(lower_pct: f64, upper_pct: f64, len: u32) {
+    let len = clamp_len(len);
+    if !(0.0 <= lower_pct && lower_pct < upper_pct && upper_pct <= 100.0) {
+        set_error(ERR_INVALID_ARG);
+        return;
+    }
+    let mut nans = 0;
+    unsafe {
+        for i in 0..len {
+            nans += BUFFER_A.get(i).is_nan() as usize;
+        }
+    }
+    let lo = percentile_of(lower_pct, len, len - nans);
+    let hi = percentile_of(upper_pct, len, len - nans);
+    unsafe {
+        for i in 0..len {
+            BUFFER_A.set(i, clamp_value(BUFFER_A.get(i), lo, hi));
+        }
+    }
+}
+
+// Number of elements of BUFFER_A that are not exactly +/-0.0. NaN counts as
+// nonzero.
+#[no_mangle]
+pub extern "C" fn count_nonzero(len: u32) -> u32 {
+    let len = clamp_len(len);
+    let mut n = 0;
+    unsafe {
+        for i in 0..len {
+            n += (BUFFER_A.get(i) != 0.0) as u32;
+        }
+    }
+    n
+}
+
+// Number of elements strictly greater than threshold. NaN never counts.
+#[no_mangle]
+pub extern "C" fn count_above(threshold: f64, len: u32) -> u32 {
+    let len = clamp_len(len);
+    let mut n = 0;
+    unsafe {
+        for i in 0..len {
+            n += (BUFFER_A.get(i) > threshold) as u32;
+        }
+    }
+    n
+}
+
+// Write the indices i with BUFFER_A[i] > threshold, ascending, into INDEX
+// and return how many there are (count_above's value). INDEX has CAPACITY
+// slots and len is clamped to CAPACITY, so every match always fits. NaN
+// never matches. The result feeds straight into gather.
+#[no_mangle]
+pub extern "C" fn find_above(threshold: f64, len: u32) -> u32 {
+    let len = clamp_len(len);
+    let mut n = 0;
+    unsafe {
+        for i in 0..len {
+            if BUFFER_A.get(i) > threshold {
+                INDEX.set(n, i as u32);
+                n += 1;
+            }
+        }
+    }
+    n as u32
+}
+
+// Number of elements strictly less than threshold. NaN never counts.
+#[no_mangle]
+pub extern "C" fn count_below(threshold: f64, len: u32) -> u32 {
+    let len = clamp_len(len);
+    let mut n = 0;
+    unsafe {
+        for i in 0..len {
+            n += (BUFFER_A.get(i) < threshold) as u32;
+        }
+    }
+    n
+}
+
+// Number of maximal runs of equal consecutive values in BUFFER_A, by exact
+// f64 equality: -0.0 and 0.0 share a run, and every NaN starts a new one.
+// 0 for len == 0.
+#[no_mangle]
+pub extern "C" fn count_runs(len: u32) -> u32 {
+    count_runs_tol(0.0, len)
+}
+
+// Like count_runs, but a value stays in the current run while it is within
+// tol of the value that started the run (|x - start| <= tol).
+#[no_mangle]
+pub extern "C" fn count_runs_tol(tol: f64, len: u32) -> u32 {
+    let len = clamp_len(len);
+    if len == 0 {
+        return 0;
+    }
+    let mut runs = 1;
+    unsafe {
+        let mut start = BUFFER_A.get(0);
+        for i in 1..len {
+            let x = BUFFER_A.get(i);
+            if !(x == start || (x - start).abs() <= tol) {
+                runs += 1;
+                start = x;
+            }
+        }
+    }
+    runs
+}
+
+// Guard-rail predicates over BUFFER_A, returning 1 or 0. Each stops at the
+// first element that decides the answer. all_finite is 1 for len == 0.
+#[no_mangle]
+pub extern "C" fn all_finite(len: u32) -> u32 {
+    let len = clamp_len(len);
+    unsafe {
+        for i in 0..len {
+            if !BUFFER_A.get(i).is_finite() {
+                return 0;
+            }
+        }
+    }
+    1
+}
+
+#[no_mangle]
+pub extern "C" fn any_nan(len: u32) -> u32 {
+    let len = clamp_len(len);
+    unsafe {
+        for i in 0..len {
+            if BUFFER_A.get(i).is_nan() {
+                return 1;
+            }
+        }
+    }
+    0
+}
+
+// Strictly below zero: -0.0 and NaN do not count as negative.
+#[no_mangle]
+pub extern "C" fn any_negative(len: u32) -> u32 {
+    let len = clamp_len(len);
+    unsafe {
+        for i in 0..len {
+            if BUFFER_A.get(i) < 0.0 {
+                return 1;
+            }
+        }
+    }
+    0
+}
+
+// Count BUFFER_A[0..len] into bins equal-width buckets over [lo, hi],
+// writing the counts (as f64) into RESULT[0..bins]. Buckets are half-open
+// except the last, which includes hi. Values outside the range are clamped
+// into the first/last bucket; NaNs are dropped. If hi <= lo every value
+// lands in bucket 0.
+fn histogram_into(lo: f64, hi: f64, bins: usize, len: usize) {
+    if bins == 0 {
+        return;
+    }
+    let scale = if hi > lo {
+        bins as f64 / (hi - lo)
+    } else {
+        0.0
+    };
+    unsafe {
+        RESULT.fill(bins, 0.0);
+        for i in 0..len {
+            let x = BUFFER_A.get(i);
+            if x.is_nan() {
+                continue;
+            }
+            // float -> usize casts saturate, so below-range values land in 0
+            let b = (((x - lo) * scale) as usize).min(bins - 1);
+            RESULT.set(b, RESULT.get(b) + 1.0);
+        }
+    }
+}
+
+// Histogram of BUFFER_A over [min, max] into RESULT[0..bins]. See
+// histogram_into for bucket edges and out-of-range handling. bins is
+// clamped to CAPACITY.
+#[no_mangle]
+pub extern "C" fn histogram(min: f64, max: f64, bins: u32, len: u32) {
+    let len = clamp_len(len);
+    let bins = clamp_len(bins);
+    histogram_into(min, max, bins, len);
+}
+
+// Histogram over the data's own range: finds the min and max of BUFFER_A
+// (ignoring NaN), bins into RESULT[0..bins] as histogram_into does, and
+// writes the detected min and max to RESULT[bins] and RESULT[bins + 1] so
+// the host can rebuild the edges. Constant data (min == max) all lands in
+// bin 0. With no non-NaN values the counts are all zero and min/max come
+// back as +inf/-inf. Does nothing if bins + 2 exceeds CAPACITY.
+#[no_mangle]
+pub extern "C" fn histogram_auto(bins: u32, len: u32) {
+    let len = clamp_len(len);
+    let bins = bins as usize;
+    if bins > CAPACITY - 2 {
+        set_error(ERR_DIMENSION);
+        return;
+    }
+    let mut lo = f64::INFINITY;
+    let mut hi = f64::NEG_INFINITY;
+    unsafe {
+        for i in 0..len {
+            let x = BUFFER_A.get(i);
+            lo = lo.min(x);
+            hi = hi.max(x);
+        }
+    }
+    histogram_into(lo, hi, bins, len);
+    unsafe {
+        RESULT.set(bins, lo);
+        RESULT.set(bins + 1, hi);
+    }
+}
+
+// In-place activations over BUFFER_A. NaN passes through all of them.
+#[no_mangle]
+pub extern "C" fn relu_a(len: u32) {
+    let len = clamp_len(len);
+    unsafe {
+        for i in 0..len {
+            let x = BUFFER_A.get(i);
+            BUFFER_A.set(i, if x < 0.0 { 0.0 } else { x });
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn leaky_relu_a(alpha: f64, len: u32) {
+    let len = clamp_len(len);
+    unsafe {
+        for i in 0..len {
+            let x = BUFFER_A.get(i);
+            BUFFER_A.set(i, if x < 0.0 { alpha * x } else { x });
+        }
+    }
+}
+
+// 1 / (1 + exp(-x)), evaluated as exp(x) / (1 + exp(x)) for negative x so
+// exp never overflows.
+#[no_mangle]
+pub extern "C" fn sigmoid_a(len: u32) {
+    let len = clamp_len(len);
+    unsafe {
+        for i in 0..len {
+            let x = BUFFER_A.get(i);
+            let y = if x < 0.0 {
+                let e = libm::exp(x);
+                e / (1.0 + e)
+            } else {
+                1.0 / (1.0 + libm::exp(-x))
+            };
+            BUFFER_A.set(i, y);
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn tanh_a(len: u32) {
+    let len = clamp_len(len);
+    unsafe {
+        for i in 0..len {
+            BUFFER_A.set(i, libm::tanh(BUFFER_A.get(i)));
+        }
+    }
+}
+
+// Numerically stable softmax of BUFFER_A in place: subtract the max, then
+// exponentiate and sum in one pass, then divide. No-op for len == 0.
+#[no_mangle]
+pub extern "C" fn softmax_a(len: u32) {
+    let len = clamp_len(len);
+    if len == 0 {
+        return;
+    }
+    let m = max(len as u32);
+    let mut s = 0.0;
+    unsafe {
+        for i in 0..len {
+            let e = libm::exp(BUFFER_A.get(i) - m);
+            BUFFER_A.set(i, e);
+            s += e;
+        }
+        for i in 0..len {
+            BUFFER_A.set(i, BUFFER_A.get(i) / s);
+        }
+    }
+}
+
+// log(sum(exp(A[i]))) computed stably as max + ln(sum(exp(x - max))).
+// -inf for len == 0 (or all -inf), +inf if any element is +inf, NaN if any
+// is NaN.
+#[no_mangle]
+pub extern "C" fn logsumexp(len: u32) -> f64 {
+    let len = clamp_len(len);
+    let m = max(len as u32);
+    if m.is_infinite() {
+        return m;
+    }
+    let mut s = 0.0;
+    unsafe {
+        for i in 0..len {
+            s += libm::exp(BUFFER_A.get(i) - m);
+        }
+    }
+    m + libm::log(s)
+}
+
+// rows * cols if a matrix of that shape fits in one buffer, else None.
+// Multiplies in u64 so large dimensions can't wrap on wasm32.
+fn matrix_len(rows: u32, cols: u32) -> Option<usize> {
+    let n = rows as u64 * cols as u64;
+    if n <= CAPACITY as u64 {
+        Some(n as usize)
+    } else {
+        set_error(ERR_DIMENSION);
+        None
+    }
+}
+
+// RESULT (m x n) = BUFFER_A (m x k) * BUFFER_B (k x n), all row-major. Does
+// nothing if any of the three matrices would not fit in CAPACITY. Uses ikj
+// order so the inner loop streams rows of B and RESULT, with a plain multiply
+// and add like dot4 rather than the software fma.
+#[no_mangle]
+pub extern "C" fn matmul(m: u32, k: u32, n: u32) {
+    let (Some(_), Some(_), Some(mn)) = (matrix_len(m, k), matrix_len(k, n), matrix_len(m, n))
+    else {
+        return;
+    };
+    let (m, k, n) = (m as usize, k as usize, n as usize);
+    unsafe {
+        RESULT.fill(mn, 0.0);
+        for i in 0..m {
+            for p in 0..k {
+                let a = BUFFER_A.get(i * k + p);
+                for j in 0..n {
+                    let r = i * n + j;
+                    RESULT.set(r, RESULT.get(r) + a * BUFFER_B.get(p * n + j));
+                }
+            }
+        }
+    }
+}
+
+// Matrix-vector product: BUFFER_A is rows x cols (row-major), BUFFER_B the
+// length-cols vector, and RESULT[r] the dot of row r with B. Each row goes
+// through dot4 (plain multiplies, not mul_add - see dot_simd). Does nothing
+// if rows * cols or rows exceeds CAPACITY; the second check matters when cols
+// is 0.
+#[no_mangle]
+pub extern "C" fn matvec(rows: u32, cols: u32) {
+    if matrix_len(rows, cols).is_none() || matrix_len(rows, 1).is_none() {
+        return;
+    }
+    let (rows, cols) = (rows as usize, cols as usize);
+    let (a, b) = (BUFFER_A.as_ptr(), BUFFER_B.as_ptr());
+    unsafe {
+        for r in 0..rows {
+            RESULT.set(r, dot4(a.add(r * cols), b, cols));
+        }
+    }
+}
+
+// Write the transpose of BUFFER_A (rows x cols, row-major) into RESULT
+// (cols x rows). Does nothing if rows * cols exceeds CAPACITY. Works in
+// 32x32 tiles so both the reads and the strided writes stay cache-resident.
+#[no_mangle]
+pub extern "C" fn transpose(rows: u32, cols: u32) {
+    const TILE: usize = 32;
+    if matrix_len(rows, cols).is_none() {
+        return;
+    }
+    let (rows, cols) = (rows as usize, cols as usize);
+    unsafe {
+        for r0 in (0..rows).step_by(TILE) {
+            for c0 in (0..cols).step_by(TILE) {
+                for r in r0..(r0 + TILE).min(rows) {
+                    for c in c0..(c0 + TILE).min(cols) {
+                        RESULT.set(c * rows + r, BUFFER_A.get(r * cols + c));
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Valid-mode cross-correlation of the signal in BUFFER_A with the kernel in
+// BUFFER_B: RESULT[i] = sum_j A[i+j] * B[j] for the signal_len - kernel_len
+// + 1 positions where the kernel fits entirely. The kernel is not flipped;
+// reverse it first for a true convolution. Writes nothing if kernel_len is
+// 0 or longer than the signal.
+#[no_mangle]
+pub extern "C" fn conv1d(signal_len: u32, kernel_len: u32) {
+    let signal_len = clamp_len(signal_len);
+    let kernel_len = clamp_len(kernel_len);
+    if kernel_len == 0 || kernel_len > signal_len {
+        set_error(ERR_DIMENSION);
+        return;
+    }
+    unsafe {
+        for i in 0..=(signal_len - kernel_len) {
+            let mut acc = 0.0;
+            for j in 0..kernel_len {
+                acc = mul_add(BUFFER_A.get(i + j), BUFFER_B.get(j), acc);
+            }
+            RESULT.set(i, acc);
+        }
+    }
+}
+
+// Unnormalized autocorrelation of BUFFER_A[0..len] for lags 0..=max_lag:
+// RESULT[lag] = sum over i of A[i] * A[i + lag]. O(len * max_lag). Does
+// nothing unless max_lag < len.
+#[no_mangle]
+pub extern "C" fn autocorr(max_lag: u32, len: u32) {
+    let len = clamp_len(len);
+    let max_lag = max_lag as usize;
+    if max_lag >= len {
+        set_error(ERR_DIMENSION);
+        return;
+    }
+    let a = BUFFER_A.as_ptr();
+    unsafe {
+        for lag in 0..=max_lag {
+            RESULT.set(lag, dot4(a, a.add(lag), len - lag));
+        }
+    }
+}
+
+// Trapezoidal-rule integral of BUFFER_A sampled at uniform spacing dx:
+// dx * (A[0]/2 + A[1] + ... + A[len-2] + A[len-1]/2). 0.0 for len < 2.
+#[no_mangle]
+pub extern "C" fn trapz(dx: f64, len: u32) -> f64 {
+    let len = clamp_len(len);
+    if len < 2 {
+        return 0.0;
+    }
+    let mut s = 0.0;
+    unsafe {
+        for i in 1..len - 1 {
+            s += BUFFER_A.get(i);
+        }
+        s += 0.5 * (BUFFER_A.get(0) + BUFFER_A.get(len - 1));
+    }
+    dx * s
+}
+
+// Running trapezoidal integral of BUFFER_A into RESULT: RESULT[0] = 0 and
+// RESULT[i] = RESULT[i-1] + (A[i-1] + A[i]) / 2 * dx, so RESULT[len-1] equals
+// trapz(dx, len) up to rounding. Writes len outputs.
+#[no_mangle]
+pub extern "C" fn cumtrapz(dx: f64, len: u32) {
+    let len = clamp_len(len);
+    if len == 0 {
+        return;
+    }
+    let mut s = 0.0;
+    unsafe {
+        RESULT.set(0, 0.0);
+        for i in 1..len {
+            s += 0.5 * (BUFFER_A.get(i - 1) + BUFFER_A.get(i)) * dx;
+            RESULT.set(i, s);
+        }
+    }
+}
+
+// Composite Simpson's rule: dx/3 * (A[0] + 4*A[1] + 2*A[2] + ... + 4*A[len-2]
+// + A[len-1]), which needs an odd number of samples. For even len, Simpson
+// covers the first len - 1 samples and the last interval adds a trapezoid.
+// len == 2 is a plain trapezoid; 0.0 for len < 2.
+#[no_mangle]
+pub extern "C" fn simpson(dx: f64, len: u32) -> f64 {
+    let len = clamp_len(len);
+    if len < 3 {
+        return trapz(dx, len as u32);
+    }
+    let n = if len % 2 == 1 { len } else { len - 1 };
+    let mut s = 0.0;
+    unsafe {
+        s += BUFFER_A.get(0) + BUFFER_A.get(n - 1);
+        for i in 1..n - 1 {
+            s += if i % 2 == 1 { 4.0 } else { 2.0 } * BUFFER_A.get(i);
+        }
+        let mut total = dx / 3.0 * s;
+        if n < len {
+            total += 0.5 * dx * (BUFFER_A.get(len - 2) + BUFFER_A.get(len - 1));
+        }
+        total
+    }
+}
+
+// First derivative of BUFFER_A into RESULT, numpy.gradient style: central
+// differences (A[i+1] - A[i-1]) / (2*dx) inside, one-sided first
+// differences at both ends. len < 2 writes zeros.
+#[no_mangle]
+pub extern "C" fn gradient(dx: f64, len: u32) {
+    let len = clamp_len(len);
+    unsafe {
+        if len < 2 {
+            RESULT.fill(len, 0.0);
+            return;
+        }
+        RESULT.set(0, (BUFFER_A.get(1) - BUFFER_A.get(0)) / dx);
+        for i in 1..len - 1 {
+            RESULT.set(i, (BUFFER_A.get(i + 1) - BUFFER_A.get(i - 1)) / (2.0 * dx));
+        }
+        let last = len - 1;
+        RESULT.set(last, (BUFFER_A.get(last) - BUFFER_A.get(last - 1)) / dx);
+    }
+}
+
+// First differences in place: A[i] = A[i+1] - A[i] for i in 0..len-1, so the
+// len - 1 results are packed at the front of BUFFER_A (A[len-1] is left
+// as it was). Returns the new length len - 1, or 0 for len < 2.
+#[no_mangle]
+pub extern "C" fn diff_a(len: u32) -> u32 {
+    let len = clamp_len(len);
+    if len < 2 {
+        return 0;
+    }
+    unsafe {
+        for i in 0..len - 1 {
+            BUFFER_A.set(i, BUFFER_A.get(i + 1) - BUFFER_A.get(i));
+        }
+    }
+    (len - 1) as u32
+}
+
+// Same as diff_a, but writes RESULT[0..len-1] and leaves BUFFER_A intact.
+#[no_mangle]
+pub extern "C" fn diff_to_result(len: u32) -> u32 {
+    let len = clamp_len(len);
+    if len < 2 {
+        return 0;
+    }
+    unsafe {
+        for i in 0..len - 1 {
+            RESULT.set(i, BUFFER_A.get(i + 1) - BUFFER_A.get(i));
+        }
+    }
+    (len - 1) as u32
+}
+
+// Evaluate the polynomial with coefficients BUFFER_B[0..ncoef] (highest
+// degree first, as numpy.polyval) at each BUFFER_A[0..npoints], into RESULT.
+// Horner's method with one mul_add per coefficient. ncoef == 0 is the zero
+// polynomial. Does nothing if ncoef or npoints exceeds CAPACITY.
+#[no_mangle]
+pub extern "C" fn polyval(ncoef: u32, npoints: u32) {
+    if matrix_len(ncoef, 1).is_none() || matrix_len(npoints, 1).is_none() {
+        return;
+    }
+    let (ncoef, npoints) = (ncoef as usize, npoints as usize);
+    unsafe {
+        for i in 0..npoints {
+            let x = BUFFER_A.get(i);
+            let mut y = 0.0;
+            for k in 0..ncoef {
+                y = mul_add(y, x, BUFFER_B.get(k));
+            }
+            RESULT.set(i, y);
+        }
+    }
+}
+
+// Trailing moving average of BUFFER_A into RESULT: RESULT[i] is the mean of
+// A[i..i+window], giving len - window + 1 outputs. O(n) sliding running sum.
+// Writes nothing if window is 0 or larger than len.
+#[no_mangle]
+pub extern "C" fn moving_average(window: u32, len: u32) {
+    let len = clamp_len(len);
+    let window = window as usize;
+    if window == 0 || window > len {
+        set_error(ERR_DIMENSION);
+        return;
+    }
+    let w = window as f64;
+    unsafe {
+        let mut s = 0.0;
+        for i in 0..window {
+            s += BUFFER_A.get(i);
+        }
+        RESULT.set(0, s / w);
+        for i in window..len {
+            s += BUFFER_A.get(i) - BUFFER_A.get(i - window);
+            RESULT.set(i - window + 1, s / w);
+        }
+    }
+}
+
+// Same-length moving average: RESULT[i] is the mean of the up-to-window
+// elements ending at i, A[i+1-window..=i]. The first window - 1 outputs
+// average only the elements available so far, so RESULT lines up
+// index-for-index with BUFFER_A. Writes nothing if window is 0.
+#[no_mangle]
+pub extern "C" fn moving_average_same(window: u32, len: u32) {
+    let len = clamp_len(len);
+    let window = window as usize;
+    if window == 0 {
+        set_error(ERR_DIMENSION);
+        return;
+    }
+    let mut s = 0.0;
+    unsafe {
+        for i in 0..len {
+            s += BUFFER_A.get(i);
+            if i >= window {
+                s -= BUFFER_A.get(i - window);
+            }
+            RESULT.set(i, s / (i + 1).min(window) as f64);
+        }
+    }
+}
+
+// Rolling population standard deviation: RESULT[i] is the stddev of
+// A[i..i+window], giving len - window + 1 outputs. Each step slides a
+// Welford state (mean, M2) by swapping the outgoing element for the incoming
+// one, which is far better conditioned than running sum / sum-of-squares but
+// still accumulates rounding. To bound that drift the state is recomputed
+// from scratch every window steps, which costs one extra pass over the data
+// in total. Writes nothing if window is 0 or larger than len.
+#[no_mangle]
+pub extern "C" fn rolling_std(window: u32, len: u32) {
+    let len = clamp_len(len);
+    let window = window as usize;
+    if window == 0 || window > len {
+        set_error(ERR_DIMENSION);
+        return;
+    }
+    let w = window as f64;
+    let (mut mean, mut m2) = welford_range(0, window);
+    unsafe {
+        RESULT.set(0, libm::sqrt(m2.max(0.0) / w));
+        for i in window..len {
+            let start = i - window + 1;
+            if start.is_multiple_of(window) {
+                (mean, m2) = welford_range(start, i + 1);
+            } else {
+                let (x_in, x_out) = (BUFFER_A.get(i), BUFFER_A.get(i - window));
+                let new_mean = mean + (x_in - x_out) / w;
+                m2 += (x_in - x_out) * (x_in - new_mean + x_out - mean);
+                mean = new_mean;
+            }
+            RESULT.set(start, libm::sqrt(m2.max(0.0) / w));
+        }
+    }
+}
+
+// Exponential moving average of BUFFER_A in place: y[0] = x[0], y[i] =
+// alpha * x[i] + (1 - alpha) * y[i-1], evaluated as y[i-1] + alpha * (x[i] -
+// y[i-1]). A sequential scan, so it doesn't vectorize. alpha is meant to be
+// in [0, 1]; values outside it are not rejected and amplify or oscillate.
+#[no_mangle]
+pub extern "C" fn ema(alpha: f64, len: u32) {
+    let len = clamp_len(len);
+    if len == 0 {
+        return;
+    }
+    unsafe {
+        let mut y = BUFFER_A.get(0);
+        for i in 1..len {
+            y = mul_add(alpha, BUFFER_A.get(i) - y, y);
+            BUFFER_A.set(i, y);
+        }
+    }
+}
+
+// RESULT[j] = A[j * factor], i.e. A[..len][::factor]: keeps A[0] and every
+// factor-th element after it. Returns the output count, ceil(len / factor).
+// factor == 0 writes nothing and returns 0.
+#[no_mangle]
+pub extern "C" fn decimate(factor: u32, len: u32) -> u32 {
+    let len = clamp_len(len);
+    let factor = factor as usize;
+    if factor == 0 {
+        set_error(ERR_DIMENSION);
+        return 0;
+    }
+    let n = len.div_ceil(factor);
+    unsafe {
+        for j in 0..n {
+            RESULT.set(j, BUFFER_A.get(j * factor));
+        }
+    }
+    n as u32
+}
+
+// RESULT[j] = mean of the block A[j * factor..(j + 1) * factor]. A partial
+// final block (when factor doesn't divide len) is averaged over just the
+// elements it has, so the output count is ceil(len / factor). factor == 0
+// writes nothing and returns 0.
+#[no_mangle]
+pub extern "C" fn downsample_mean(factor: u32, len: u32) -> u32 {
+    let len = clamp_len(len);
+    let factor = factor as usize;
+    if factor == 0 {
+        set_error(ERR_DIMENSION);
+        return 0;
+    }
+    let n = len.div_ceil(factor);
+    unsafe {
+        for j in 0..n {
+            let (start, end) = (j * factor, ((j + 1) * factor).min(len));
+            let mut s = 0.0;
+            for i in start..end {
+                s += BUFFER_A.get(i);
+            }
+            RESULT.set(j, s / (end - start) as f64);
+        }
+    }
+    n as u32
+}
+
+// RESULT[j] = sum of the block A[j * block_size..(j + 1) * block_size], with
+// a partial final block when block_size doesn't divide len. Returns the
+// block count, ceil(len / block_size). block_size == 0 writes nothing and
+// returns 0.
+#[no_mangle]
+pub extern "C" fn block_sum(block_size: u32, len: u32) -> u32 {
+    let len = clamp_len(len);
+    let size = block_size as usize;
+    if size == 0 {
+        set_error(ERR_DIMENSION);
+        return 0;
+    }
+    let n = len.div_ceil(size);
+    unsafe {
+        for j in 0..n {
+            let mut s = 0.0;
+            for i in j * size..((j + 1) * size).min(len) {
+                s += BUFFER_A.get(i);
+            }
+            RESULT.set(j, s);
+        }
+    }
+    n as u32
+}
+
+// Resample BUFFER_A[0..in_len] to out_len points in RESULT by linear
+// interpolation. Endpoints are aligned: output j sits at input position
+// j * (in_len - 1) / (out_len - 1), so RESULT[0] = A[0] and RESULT[out_len -
+// 1] = A[in_len - 1]. Does nothing if in_len is 0 or out_len exceeds
+// CAPACITY.
+#[no_mangle]
+pub extern "C" fn upsample_linear(out_len: u32, in_len: u32) {
+    let in_len = clamp_len(in_len);
+    if in_len == 0 || matrix_len(out_len, 1).is_none() {
+        set_error(ERR_DIMENSION);
+        return;
+    }
+    let out_len = out_len as usize;
+    let step = if out_len > 1 {
+        (in_len - 1) as f64 / (out_len - 1) as f64
+    } else {
+        0.0
+    };
+    unsafe {
+        for j in 0..out_len {
+            let pos = j as f64 * step;
+            let i = (pos as usize).min(in_len - 1);
+            let y = if i + 1 < in_len {
+                let (a, b) = (BUFFER_A.get(i), BUFFER_A.get(i + 1));
+                mul_add(pos - i as f64, b - a, a)
+            } else {
+                BUFFER_A.get(i)
+            };
+            RESULT.set(j, y);
+        }
+    }
+}
+
+// sum(A * W) / sum(W) with the weights W in BUFFER_B, both sums taken in a
+// single pass. NaN if the weights sum to zero.
+#[no_mangle]
+pub extern "C" fn weighted_mean(len: u32) -> f64 {
+    let len = clamp_len(len);
+    let mut num = 0.0;
+    let mut den = 0.0;
+    unsafe {
+        for i in 0..len {
+            let w = BUFFER_B.get(i);
+            num += BUFFER_A.get(i) * w;
+            den += w;
+        }
+    }
+    if den == 0.0 {
+        return f64::NAN;
+    }
+    num / den
+}
+
+// One-pass accumulators for simple linear regression of B (y) on A (x),
+// written to RESULT[0..6] as [n, sum(x), sum(y), sum(x^2), sum(y^2),
+// sum(x*y)]. n is len after clamping, as an f64. Raw sums are fine for the
+// closed-form slope on well-scaled data; on data far from the origin, center
+// it first (e.g. with standardize) to avoid cancellation.
+#[no_mangle]
+pub extern "C" fn regression_stats(len: u32) {
+    let len = clamp_len(len);
+    let (mut sx, mut sy, mut sxx, mut syy, mut sxy) = (0.0, 0.0, 0.0, 0.0, 0.0);
+    unsafe {
+        for i in 0..len {
+            let (x, y) = (BUFFER_A.get(i), BUFFER_B.get(i));
+            sx += x;
+            sy += y;
+            sxx += x * x;
+            syy += y * y;
+            sxy += x * y;
+        }
+        RESULT.set(0, len as f64);
+        RESULT.set(1, sx);
+        RESULT.set(2, sy);
+        RESULT.set(3, sxx);
+        RESULT.set(4, syy);
+        RESULT.set(5, sxy);
+    }
+}
+
+// Fill BUFFER_A[0..len] with len evenly spaced values from start to stop
+// inclusive. The last element is exactly stop; len == 1 writes just start.
+#[no_mangle]
+pub extern "C" fn linspace(start: f64, stop: f64, len: u32) {
+    let len = clamp_len(len);
+    if len == 0 {
+        return;
+    }
+    let step = if len > 1 {
+        (stop - start) / (len - 1) as f64
+    } else {
+        0.0
+    };
+    unsafe {
+        for i in 0..len {
+            BUFFER_A.set(i, start + i as f64 * step);
+        }
+        if len > 1 {
+            BUFFER_A.set(len - 1, stop);
+        }
+    }
+}
+
+// Fill BUFFER_A[0..len] with start + i * step. Computed from i rather than
+// by repeated addition, so rounding error doesn't accumulate.
+#[no_mangle]
+pub extern "C" fn arange(start: f64, step: f64, len: u32) {
+    let len = clamp_len(len);
+    unsafe {
+        for i in 0..len {
+            BUFFER_A.set(i, start + i as f64 * step);
+        }
+    }
+}
+
+// Reseed the module PRNG (SplitMix64). The same seed always reproduces the
+// same sequence of random_fill_* output.
+#[no_mangle]
+pub extern "C" fn set_seed(seed: u64) {
+    RNG_STATE.set(seed);
+}
+
+// Fill BUFFER_A[0..len] with uniform values in [0, 1).
+#[no_mangle]
+pub extern "C" fn random_fill_a(len: u32) {
+    let len = clamp_len(len);
+    unsafe {
+        for i in 0..len {
+            BUFFER_A.set(i, next_f64());
+        }
+    }
+}
+
+// Fill BUFFER_A[0..len] with uniform values in [lo, hi).
+#[no_mangle]
+pub extern "C" fn random_fill_a_range(lo: f64, hi: f64, len: u32) {
+    let len = clamp_len(len);
+    let width = hi - lo;
+    unsafe {
+        for i in 0..len {
+            BUFFER_A.set(i, lo + width * next_f64());
+        }
+    }
+}
+
+// Uniform integer in [0, n) by widening multiply (Lemire). The bias is at
+// most n / 2^64, far below anything a CAPACITY-sized shuffle could observe.
+fn next_below(n: usize) -> usize {
+    ((next_u64() as u128 * n as u128) >> 64) as usize
+}
+
+// Fisher-Yates shuffle of BUFFER_A[0..len] in place, driven by the module
+// PRNG: the same set_seed gives the same permutation.
+#[no_mangle]
+pub extern "C" fn shuffle_a(len: u32) {
+    let len = clamp_len(len);
+    let a = unsafe { BUFFER_A.as_mut_slice(len) };
+    for i in (1..len).rev() {
+        a.swap(i, next_below(i + 1));
+    }
+}
+
+// Write a random permutation of 0..len into INDEX (Fisher-Yates over the
+// identity), so the host can apply one shuffle to several arrays via gather.
+#[no_mangle]
+pub extern "C" fn permute_indices(len: u32) {
+    let len = clamp_len(len);
+    let idx = unsafe { INDEX.as_mut_slice(len) };
+    for (i, x) in idx.iter_mut().enumerate() {
+        *x = i as u32;
+    }
+    for i in (1..len).rev() {
+        idx.swap(i, next_below(i + 1));
+    }
+}
+
+// Dynamic region for inputs larger than CAPACITY, carved out of linear
+// memory by memory.grow. There is only ever one region; it is extended in
+// place when it sits at the end of memory, otherwise a fresh one is grown
+// (the old one is simply abandoned - there is no allocator to free into).
+const PAGE_SIZE: usize = 65536;
+static DYN_OFFSET: StaticCell<usize> = StaticCell::new(0);
+static DYN_LEN: StaticCell<usize> = StaticCell::new(0);
+// Total bytes reserve has grown, abandoned regions included
+static DYN_GROWN: StaticCell<usize> = StaticCell::new(0);
+
+#[cfg(target_arch = "wasm32")]
+fn memory_pages() -> usize {
+    core::arch::wasm32::memory_size::<0>()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn memory_pages() -> usize {
+    0
+}
+
+// Grow linear memory by `pages`, returning the previous size in pages.
+#[cfg(target_arch = "wasm32")]
+fn grow_pages(pages: usize) -> Option<usize> {
+    match core::arch::wasm32::memory_grow::<0>(pages) {
+        usize::MAX => None,
+        prev => Some(prev),
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn grow_pages(_pages: usize) -> Option<usize> {
+    None
+}
+
+// Ensure a dynamic region of at least `elements` f64s exists and return its
+// byte offset, or 0 if memory could not be grown (or elements == 0 and no
+// region exists yet). The host copies data there and calls the *_at ops.
+// A larger reserve may move the region, so re-copy after growing.
+#[no_mangle]
+pub extern "C" fn reserve(elements: u32) -> u32 {
+    let elements = elements as usize;
+    let (offset, len) = (DYN_OFFSET.get(), DYN_LEN.get());
+    if offset != 0 && len >= elements {
+        return offset as u32;
+    }
+    if elements == 0 {
+        return 0;
+    }
+    let Some(bytes) = elements.checked_mul(8) else {
+        return 0;
+    };
+
+    let end = memory_pages() * PAGE_SIZE;
+    if offset != 0 && offset + len * 8 == end {
+        let pages = (bytes - len * 8).div_ceil(PAGE_SIZE);
+        if grow_pages(pages).is_none() {
+            return 0;
+        }
+        DYN_LEN.set(len + pages * PAGE_SIZE / 8);
+        DYN_GROWN.set(DYN_GROWN.get() + pages * PAGE_SIZE);
+        return offset as u32;
+    }
+
+    let pages = bytes.div_ceil(PAGE_SIZE);
+    let Some(prev) = grow_pages(pages) else {
+        return 0;
+    };
+    DYN_OFFSET.set(prev * PAGE_SIZE);
+    DYN_LEN.set(pages * PAGE_SIZE / 8);
+    DYN_GROWN.set(DYN_GROWN.get() + pages * PAGE_SIZE);
+    (prev * PAGE_SIZE) as u32
+}
+
+// Bytes taken by the static buffers, fixed at compile time by CAPACITY
+const STATIC_BYTES: usize = 3 * size_of::<StaticBuffer<f64>>()
+    + 3 * size_of::<StaticBuffer<f32>>()
+    + 3 * size_of::<StaticBuffer<i32>>()
+    + size_of::<StaticBuffer<i8>>()
+    + size_of::<StaticBuffer<u32>>();
+
+// Static buffers plus the current reserve()d region, in bytes. Saturates at
+// u32::MAX.
+#[no_mangle]
+pub extern "C" fn memory_bytes_used() -> u32 {
+    let n = STATIC_BYTES.saturating_add(DYN_LEN.get() * 8);
+    n.min(u32::MAX as usize) as u32
+}
+
+// High-water mark: static buffers plus every byte reserve has grown,
+// including regions abandoned when a larger one had to be grown elsewhere.
+// Linear memory never shrinks, so this only goes up.
+#[no_mangle]
+pub extern "C" fn memory_bytes_peak() -> u32 {
+    let n = STATIC_BYTES.saturating_add(DYN_GROWN.get());
+    n.min(u32::MAX as usize) as u32
+}
+
+// Current size of linear memory in 64 KiB pages (memory.size). Always 0 off
+// wasm32.
+#[no_mangle]
+pub extern "C" fn wasm_memory_pages() -> u32 {
+    memory_pages() as u32
+}
+
+// Pointer and clamped element count for [offset, offset + len*8) inside the
+// dynamic region. Offsets outside the region or not 8-byte aligned give an
+// empty span.
+fn dyn_span(offset: u32, len: u32) -> (*mut f64, usize) {
+    let offset = offset as usize;
+    let (start, end) = (DYN_OFFSET.get(), DYN_OFFSET.get() + DYN_LEN.get() * 8);
+    if start == 0 || offset < start || offset >= end || !offset.is_multiple_of(8) {
+        set_error(ERR_INDEX_RANGE);
+        return (core::ptr::null_mut(), 0);
+    }
+    let fit = (end - offset) / 8;
+    if len as usize > fit {
+        set_error(ERR_LEN_CLAMPED);
+        return (offset as *mut f64, fit);
+    }
+    (offset as *mut f64, len as usize)
+}
+
+// Sum of len f64s at byte offset inside the reserve()d region.
+#[no_mangle]
+pub extern "C" fn sum_at(offset: u32, len: u32) -> f64 {
+    let (p, n) = dyn_span(offset, len);
+    let mut s = 0.0;
+    unsafe {
+        for i in 0..n {
+            s += *p.add(i);
+        }
+    }
+    s
+}
+
+// Dot product of two len-element vectors at byte offsets inside the
+// reserve()d region. len is clamped so neither side runs past the region.
+#[no_mangle]
+pub extern "C" fn dot_at(offset_a: u32, offset_b: u32, len: u32) -> f64 {
+    let (pa, na) = dyn_span(offset_a, len);
+    let (pb, nb) = dyn_span(offset_b, len);
+    let mut d = 0.0;
+    unsafe {
+        for i in 0..na.min(nb) {
+            d += *pa.add(i) * *pb.add(i);
+        }
+    }
+    d
+}
+
+// Scale len f64s at byte offset inside the reserve()d region, in place.
+#[no_mangle]
+pub extern "C" fn scale_at(scalar: f64, offset: u32, len: u32) {
+    let (p, n) = dyn_span(offset, len);
+    unsafe {
+        for i in 0..n {
+            *p.add(i) *= scalar;
+        }
+    }
+}
+
+// Reverse the byte order of each f64 in BUFFER_A[0..len], for data produced
+// on a big-endian machine. Its own inverse; applying it to data already in
+// native layout scrambles it.
+#[no_mangle]
+pub extern "C" fn bswap_a(len: u32) {
+    let len = clamp_len(len);
+    unsafe {
+        for i in 0..len {
+            let x = BUFFER_A.get(i);
+            BUFFER_A.set(i, f64::from_bits(x.to_bits().swap_bytes()));
+        }
+    }
+}
+
+// Affine int8 quantization of BUFFER_A into BUFFER_I8:
+// q = round(x / scale) + zero_point, saturated to [-128, 127]. Rounding is
+// half-to-even (2.5 -> 2, 3.5 -> 4). NaN quantizes to zero_point.
+#[no_mangle]
+pub extern "C" fn quantize_i8(scale: f64, zero_point: i32, len: u32) {
+    let len = clamp_len(len);
+    let zp = zero_point as f64;
+    unsafe {
+        for i in 0..len {
+            let q = libm::roundeven(BUFFER_A.get(i) / scale) + zp;
+            let q = if q.is_nan() { zp } else { q };
+            BUFFER_I8.set(i, clamp_value(q, -128.0, 127.0) as i8);
+        }
+    }
+}
+
+// Inverse of quantize_i8: BUFFER_A[i] = (BUFFER_I8[i] - zero_point) * scale.
+#[no_mangle]
+pub extern "C" fn dequantize_i8(scale: f64, zero_point: i32, len: u32) {
+    let len = clamp_len(len);
+    unsafe {
+        for i in 0..len {
+            let q = BUFFER_I8.get(i) as i32 - zero_point;
+            BUFFER_A.set(i, q as f64 * scale);
+        }
+    }
+}
+
+// Score the query in BUFFER_A[0..d] against each of the n rows of BUFFER_B
+// (n x d, row-major), writing the n dot products into RESULT. Does nothing
+// if d * n or n exceeds CAPACITY; the second check matters when d is 0.
+#[no_mangle]
+pub extern "C" fn batched_dot(d: u32, n: u32) {
+    if matrix_len(n, d).is_none() || matrix_len(n, 1).is_none() {
+        return;
+    }
+    let (d, n) = (d as usize, n as usize);
+    unsafe {
+        for r in 0..n {
+            RESULT.set(r, dot4(BUFFER_A.as_ptr(), BUFFER_B.as_ptr().add(r * d), d));
+        }
+    }
+}
+
+// Outer product of BUFFER_A[0..m] and BUFFER_B[0..n] into RESULT (m x n,
+// row-major): RESULT[i * n + j] = A[i] * B[j]. Does nothing if m * n exceeds
+// CAPACITY.
+#[no_mangle]
+pub extern "C" fn outer(m: u32, n: u32) {
+    if matrix_len(m, n).is_none() {
+        return;
+    }
+    let (m, n) = (m as usize, n as usize);
+    unsafe {
+        for i in 0..m {
+            let a = BUFFER_A.get(i);
+            for j in 0..n {
+                RESULT.set(i * n + j, a * BUFFER_B.get(j));
+            }
+        }
+    }
+}
+
+// Squared Euclidean distances between the n rows of BUFFER_A (n x d,
+// row-major), written as the n x n matrix RESULT[i * n + j]. Only the upper
+// triangle is computed and mirrored; the diagonal is exactly 0. Does nothing
+// if n * d or n * n exceeds CAPACITY.
+#[no_mangle]
+pub extern "C" fn pairwise_sqdist(n: u32, d: u32) {
+    if matrix_len(n, d).is_none() || matrix_len(n, n).is_none() {
+        return;
+    }
+    let (n, d) = (n as usize, d as usize);
+    unsafe {
+        for i in 0..n {
+            RESULT.set(i * n + i, 0.0);
+            for j in i + 1..n {
+                let mut s = 0.0;
+                for k in 0..d {
+                    let t = BUFFER_A.get(i * d + k) - BUFFER_A.get(j * d + k);
+                    s += t * t;
+                }
+                RESULT.set(i * n + j, s);
+                RESULT.set(j * n + i, s);
+            }
+        }
+    }
+}
+
+// n independent 3D cross products: RESULT[3k..3k+3] = A[3k..3k+3] x
+// B[3k..3k+3], for vectors stored as consecutive (x, y, z) triples. Does
+// nothing if 3 * n exceeds CAPACITY.
+#[no_mangle]
+pub extern "C" fn cross3_batch(n: u32) {
+    if matrix_len(n, 3).is_none() {
+        return;
+    }
+    unsafe {
+        for k in 0..n as usize {
+            let j = 3 * k;
+            let (ax, ay, az) = (BUFFER_A.get(j), BUFFER_A.get(j + 1), BUFFER_A.get(j + 2));
+            let (bx, by, bz) = (BUFFER_B.get(j), BUFFER_B.get(j + 1), BUFFER_B.get(j + 2));
+            RESULT.set(j, ay * bz - az * by);
+            RESULT.set(j + 1, az * bx - ax * bz);
+            RESULT.set(j + 2, ax * by - ay * bx);
+        }
+    }
+}
+
+// Cross product of BUFFER_A[0..3] and BUFFER_B[0..3] into RESULT[0..3].
+#[no_mangle]
+pub extern "C" fn cross3() {
+    cross3_batch(1);
+}
+
+// Hamilton products of n quaternion pairs: RESULT[4k..4k+4] = A_k * B_k,
+// each quaternion stored as [x, y, z, w] (vector part first, scalar last,
+// as in glTF and most game engines). As rotations, A_k * B_k applies B_k
+// first. Does nothing if 4 * n exceeds CAPACITY.
+#[no_mangle]
+pub extern "C" fn quat_mul(n: u32) {
+    if matrix_len(n, 4).is_none() {
+        return;
+    }
+    unsafe {
+        for k in 0..n as usize {
+            let j = 4 * k;
+            let (ax, ay, az, aw) = (
+                BUFFER_A.get(j),
+                BUFFER_A.get(j + 1),
+                BUFFER_A.get(j + 2),
+                BUFFER_A.get(j + 3),
+            );
+            let (bx, by, bz, bw) = (
+                BUFFER_B.get(j),
+                BUFFER_B.get(j + 1),
+                BUFFER_B.get(j + 2),
+                BUFFER_B.get(j + 3),
+            );
+            RESULT.set(j, aw * bx + ax * bw + ay * bz - az * by);
+            RESULT.set(j + 1, aw * by - ax * bz + ay * bw + az * bx);
+            RESULT.set(j + 2, aw * bz + ax * by - ay * bx + az * bw);
+            RESULT.set(j + 3, aw * bw - ax * bx - ay * by - az * bz);
+        }
+    }
+}
+
+// Neumaier (improved Kahan) compensated sum of BUFFER_A. The running
+// compensation captures the low-order bits each addition drops, so the
+// error stays near one ulp of the result regardless of len. The dependency
+// on the compensation term keeps it from vectorizing: use sum_simd when
+// throughput matters more than accuracy.
+#[no_mangle]
+pub extern "C" fn sum_kahan(len: u32) -> f64 {
+    let len = clamp_len(len);
+    let mut s = 0.0;
+    let mut c = 0.0;
+    unsafe {
+        for i in 0..len {
+            let x = BUFFER_A.get(i);
+            let t = s + x;
+            if s.abs() >= x.abs() {
+                c += (s - t) + x;
+            } else {
+                c += (x - t) + s;
+            }
+            s = t;
+        }
+    }
+    s + c
+}
+
+// Compensated dot product (Ogita-Rump-Oishi Dot2). Each product is split
+// exactly into p + e with an FMA (TwoProduct), each addition into s + e
+// with TwoSum, and the error terms are summed alongside. The result is as
+// accurate as plain dot evaluated in twice the precision and then rounded:
+// error <= u*|dot| + O(len^2 u^2) * sum|A[i]*B[i]|, where plain dot only
+// guarantees O(len u) * sum|A[i]*B[i]|. Several times slower than dot, and
+// the FMA is in software on wasm32.
+#[no_mangle]
+pub extern "C" fn dot_compensated(len: u32) -> f64 {
+    let len = clamp_len(len);
+    let mut s = 0.0;
+    let mut c = 0.0;
+    unsafe {
+        for i in 0..len {
+            let (a, b) = (BUFFER_A.get(i), BUFFER_B.get(i));
+            let p = a * b;
+            let pe = mul_add(a, b, -p);
+            let t = s + p;
+            let z = t - s;
+            c += ((s - (t - z)) + (p - z)) + pe;
+            s = t;
+        }
+    }
+    s + c
+}
+
+// Dot product in single precision, for matching reference implementations
+// that accumulate in f32 (e.g. PyTorch/TensorFlow on float32 tensors): each
+// input is rounded to f32, multiplied in f32, and summed sequentially into
+// one f32 accumulator, then widened back. Deliberately less accurate than
+// dot; frameworks that use pairwise or blocked sums may still differ in the
+// last few bits.
+#[no_mangle]
+pub extern "C" fn dot_f32_accum(len: u32) -> f64 {
+    let len = clamp_len(len);
+    let mut d: f32 = 0.0;
+    unsafe {
+        for i in 0..len {
+            d += BUFFER_A.get(i) as f32 * BUFFER_B.get(i) as f32;
+        }
+    }
+    d as f64
+}
+
+// Pairwise (cascade) dot product: split in halves recursively down to blocks
+// of at most 128, which go through dot4. Rounding error grows as O(log len)
+// rather than O(len) for a running sum, at nearly dot_simd's speed and with
+// none of dot_compensated's per-element cost. Recursion depth is
+// log2(CAPACITY / 128), so well under 20.
+#[no_mangle]
+pub extern "C" fn dot_pairwise(len: u32) -> f64 {
+    let len = clamp_len(len);
+    unsafe { dot_pairwise_at(BUFFER_A.as_ptr(), BUFFER_B.as_ptr(), len) }
+}
+
+unsafe fn dot_pairwise_at(a: *const f64, b: *const f64, len: usize) -> f64 {
+    if len <= 128 {
+        return dot4(a, b, len);
+    }
+    let h = len / 2;
+    dot_pairwise_at(a, b, h) + dot_pairwise_at(a.add(h), b.add(h), len - h)
+}
+
+// dot, with the result in RESULT[0] and the number of products actually
+// summed (len after clamping to CAPACITY) in RESULT[1], so a truncated dot
+// can't pass unnoticed.
+#[no_mangle]
+pub extern "C" fn dot_counted(len: u32) {
+    let n = clamp_len(len);
+    let d = dot(n as u32);
+    unsafe {
+        RESULT.set(0, d);
+        RESULT.set(1, n as f64);
+    }
+}
+
+// Sum of BUFFER_A skipping NaNs; 0.0 if every element is NaN.
+#[no_mangle]
+pub extern "C" fn nansum(len: u32) -> f64 {
+    let len = clamp_len(len);
+    let mut s = 0.0;
+    unsafe {
+        for i in 0..len {
+            let x = BUFFER_A.get(i);
+            if !x.is_nan() {
+                s += x;
+            }
+        }
+    }
+    s
+}
+
+// Mean of the non-NaN elements of BUFFER_A; NaN if there are none.
+#[no_mangle]
+pub extern "C" fn nanmean(len: u32) -> f64 {
+    let len = clamp_len(len);
+    let mut s = 0.0;
+    let mut n = 0usize;
+    unsafe {
+        for i in 0..len {
+            let x = BUFFER_A.get(i);
+            if !x.is_nan() {
+                s += x;
+                n += 1;
+            }
+        }
+    }
+    if n == 0 {
+        return f64::NAN;
+    }
+    s / n as f64
+}
+
+// dot, skipping every term where A[i] or B[i] is NaN, so NaN can serve as a
+// padding marker in ragged batches. 0.0 if every term is skipped.
+#[no_mangle]
+pub extern "C" fn dot_skip_nan(len: u32) -> f64 {
+    let len = clamp_len(len);
+    let mut d = 0.0;
+    unsafe {
+        for i in 0..len {
+            let (a, b) = (BUFFER_A.get(i), BUFFER_B.get(i));
+            if !a.is_nan() && !b.is_nan() {
+                d += a * b;
+            }
+        }
+    }
+    d
+}
+
+// Overwrite every NaN in BUFFER_A[0..len] with value; returns how many were
+// replaced.
+#[no_mangle]
+pub extern "C" fn replace_nan(value: f64, len: u32) -> u32 {
+    let len = clamp_len(len);
+    let mut n = 0;
+    unsafe {
+        for i in 0..len {
+            if BUFFER_A.get(i).is_nan() {
+                BUFFER_A.set(i, value);
+                n += 1;
+            }
+        }
+    }
+    n
+}
+
+// Overwrite every +inf and -inf in BUFFER_A[0..len] with value; returns how
+// many were replaced.
+#[no_mangle]
+pub extern "C" fn replace_inf(value: f64, len: u32) -> u32 {
+    let len = clamp_len(len);
+    let mut n = 0;
+    unsafe {
+        for i in 0..len {
+            if BUFFER_A.get(i).is_infinite() {
+                BUFFER_A.set(i, value);
+                n += 1;
+            }
+        }
+    }
+    n
+}
+
+// Conjugate dot product sum(conj(A_k) * B_k) over pairs complex elements
+// stored interleaved as [re0, im0, re1, im1, ...] in BUFFER_A and BUFFER_B.
+// The real part goes to RESULT[0] and the imaginary part to RESULT[1].
+// Does nothing if 2 * pairs exceeds CAPACITY.
+#[no_mangle]
+pub extern "C" fn complex_dot(pairs: u32) {
+    if matrix_len(pairs, 2).is_none() {
+        return;
+    }
+    let mut re = 0.0;
+    let mut im = 0.0;
+    unsafe {
+        for k in 0..pairs as usize {
+            let (ar, ai) = (BUFFER_A.get(2 * k), BUFFER_A.get(2 * k + 1));
+            let (br, bi) = (BUFFER_B.get(2 * k), BUFFER_B.get(2 * k + 1));
+            re += ar * br + ai * bi;
+            im += ar * bi - ai * br;
+        }
+        RESULT.set(0, re);
+        RESULT.set(1, im);
+    }
+}
+
+// Planar to interleaved: RESULT[2i] = A[i], RESULT[2i + 1] = B[i] for i in
+// 0..len, 2 * len outputs. Does nothing if 2 * len exceeds CAPACITY.
+#[no_mangle]
+pub extern "C" fn interleave2(len: u32) {
+    if matrix_len(len, 2).is_none() {
+        return;
+    }
+    unsafe {
+        for i in 0..len as usize {
+            RESULT.set(2 * i, BUFFER_A.get(i));
+            RESULT.set(2 * i + 1, BUFFER_B.get(i));
+        }
+    }
+}
+
+// Interleaved to planar, the inverse of interleave2: the len pairs in
+// BUFFER_A[0..2 * len] are split into BUFFER_B[i] = A[2i] (evens) and
+// RESULT[i] = A[2i + 1] (odds). Does nothing if 2 * len exceeds CAPACITY.
+#[no_mangle]
+pub extern "C" fn deinterleave2(len: u32) {
+    if matrix_len(len, 2).is_none() {
+        return;
+    }
+    unsafe {
+        for i in 0..len as usize {
+            BUFFER_B.set(i, BUFFER_A.get(2 * i));
+            RESULT.set(i, BUFFER_A.get(2 * i + 1));
+        }
+    }
+}
+
+// Iterative radix-2 Cooley-Tukey over n = 2^log2n complex samples
+// interleaved in BUFFER_A. inverse flips the twiddle sign and scales by 1/n.
+// Twiddles are computed per butterfly column with libm::sin/cos rather than
+// by repeated multiplication, so rounding error doesn't build up along a
+// stage. Does nothing if 2 * n exceeds CAPACITY.
+fn fft_in_place(log2n: u32, inverse: bool) {
+    let Some(n) = 1u32.checked_shl(log2n) else {
+        set_error(ERR_DIMENSION);
+        return;
+    };
+    if matrix_len(n, 2).is_none() {
+        return;
+    }
+    let n = n as usize;
+    unsafe {
+        let buf = BUFFER_A.as_mut_slice(2 * n);
+
+        // Bit-reversal permutation.
+        let mut j = 0;
+        for i in 1..n {
+            let mut bit = n >> 1;
+            while j & bit != 0 {
+                j ^= bit;
+                bit >>= 1;
+            }
+            j |= bit;
+            if i < j {
+                buf.swap(2 * i, 2 * j);
+                buf.swap(2 * i + 1, 2 * j + 1);
+            }
+        }
+
+        let sign = if inverse { 1.0 } else { -1.0 };
+        let mut m = 2;
+        while m <= n {
+            let half = m / 2;
+            let step = sign * 2.0 * core::f64::consts::PI / m as f64;
+            for j in 0..half {
+                let (wr, wi) = (libm::cos(step * j as f64), libm::sin(step * j as f64));
+                let mut k = j;
+                while k < n {
+                    let (p, q) = (2 * k, 2 * (k + half));
+                    let tr = wr * buf[q] - wi * buf[q + 1];
+                    let ti = wr * buf[q + 1] + wi * buf[q];
+                    buf[q] = buf[p] - tr;
+                    buf[q + 1] = buf[p + 1] - ti;
+                    buf[p] += tr;
+                    buf[p + 1] += ti;
+                    k += m;
+                }
+            }
+            m *= 2;
+        }
+
+        if inverse {
+            let inv = 1.0 / n as f64;
+            for x in buf.iter_mut() {
+                *x *= inv;
+            }
+        }
+    }
+}
+
+// In-place forward FFT of BUFFER_A as 2^log2n interleaved (re, im) samples.
+#[no_mangle]
+pub extern "C" fn fft(log2n: u32) {
+    fft_in_place(log2n, false);
+}
+
+// In-place inverse FFT of BUFFER_A, normalized by 1/N so ifft(fft(x)) = x.
+#[no_mangle]
+pub extern "C" fn ifft(log2n: u32) {
+    fft_in_place(log2n, true);
+}
+
+// Multiply BUFFER_A[n] in place by the generalized cosine window
+// a0 - a1*cos(2*pi*n/(N-1)) + a2*cos(4*pi*n/(N-1)), N = len. These are the
+// symmetric forms (numpy's hanning/hamming/blackman); len == 1 leaves the
+// element unscaled.
+fn apply_cosine_window(a0: f64, a1: f64, a2: f64, len: u32) {
+    let len = clamp_len(len);
+    if len < 2 {
+        return;
+    }
+    let step = 2.0 * core::f64::consts::PI / (len - 1) as f64;
+    unsafe {
+        for n in 0..len {
+            let x = step * n as f64;
+            let w = a0 - a1 * libm::cos(x) + a2 * libm::cos(2.0 * x);
+            BUFFER_A.set(n, BUFFER_A.get(n) * w);
+        }
+    }
+}
+
+// Hann: 0.5 - 0.5*cos(2*pi*n/(N-1))
+#[no_mangle]
+pub extern "C" fn apply_hann(len: u32) {
+    apply_cosine_window(0.5, 0.5, 0.0, len);
+}
+
+// Hamming: 0.54 - 0.46*cos(2*pi*n/(N-1))
+#[no_mangle]
+pub extern "C" fn apply_hamming(len: u32) {
+    apply_cosine_window(0.54, 0.46, 0.0, len);
+}
+
+// Blackman: 0.42 - 0.5*cos(2*pi*n/(N-1)) + 0.08*cos(4*pi*n/(N-1))
+#[no_mangle]
+pub extern "C" fn apply_blackman(len: u32) {
+    apply_cosine_window(0.42, 0.5, 0.08, len);
+}
+
+// RESULT[i] = BUFFER_A[INDEX[i]] for i in 0..len. Indices at or beyond
+// CAPACITY are skipped, leaving RESULT[i] unchanged and setting
+// ERR_INDEX_RANGE. Same as gather_skip.
+#[no_mangle]
+pub extern "C" fn gather(len: u32) {
+    let len = clamp_len(len);
+    unsafe {
+        for i in 0..len {
+            let j = INDEX.get(i) as usize;
+            if j < CAPACITY {
+                RESULT.set(i, BUFFER_A.get(j));
+            } else {
+                set_error(ERR_INDEX_RANGE);
+            }
+        }
+    }
+}
+
+// gather's skip policy under an explicit name: with every index out of range
+// RESULT[0..len] is left exactly as it was.
+#[no_mangle]
+pub extern "C" fn gather_skip(len: u32) {
+    gather(len);
+}
+
+// gather, but out-of-range indices are pinned to CAPACITY - 1, so every
+// RESULT[i] is written. Fully out-of-range input fills RESULT[0..len] with
+// BUFFER_A[CAPACITY - 1]. Clamping is the requested policy, not an error.
+#[no_mangle]
+pub extern "C" fn gather_clamp(len: u32) {
+    let len = clamp_len(len);
+    unsafe {
+        for i in 0..len {
+            let j = (INDEX.get(i) as usize).min(CAPACITY - 1);
+            RESULT.set(i, BUFFER_A.get(j));
+        }
+    }
+}
+
+// gather, but indices wrap modulo CAPACITY (texture-style repeat), so every
+// RESULT[i] is written. Wrapping is the requested policy, not an error.
+#[no_mangle]
+pub extern "C" fn gather_wrap(len: u32) {
+    let len = clamp_len(len);
+    unsafe {
+        for i in 0..len {
+            let j = INDEX.get(i) as usize % CAPACITY;
+            RESULT.set(i, BUFFER_A.get(j));
+        }
+    }
+}
+
+// BUFFER_A[INDEX[i]] = BUFFER_B[i]. Indices at or beyond CAPACITY are
+// skipped. With duplicate indices the last write wins.
+#[no_mangle]
+pub extern "C" fn scatter(len: u32) {
+    let len = clamp_len(len);
+    unsafe {
+        for i in 0..len {
+            let j = INDEX.get(i) as usize;
+            if j < CAPACITY {
+                BUFFER_A.set(j, BUFFER_B.get(i));
+            } else {
+                set_error(ERR_INDEX_RANGE);
+            }
+        }
+    }
+}
+
+// Sparse-dense dot product: BUFFER_A holds nnz interleaved (value, index)
+// pairs [v0, i0, v1, i1, ...] and BUFFER_B the dense vector, giving
+// sum(v_k * B[i_k]). Indices are stored as f64 and truncated toward zero;
+// ones that are negative, NaN, or at or beyond CAPACITY are skipped and set
+// ERR_INDEX_RANGE. Returns 0.0 (and does nothing) if 2 * nnz exceeds
+// CAPACITY.
+#[no_mangle]
+pub extern "C" fn sparse_dot(nnz: u32) -> f64 {
+    if matrix_len(nnz, 2).is_none() {
+        return 0.0;
+    }
+    let mut d = 0.0;
+    unsafe {
+        for k in 0..nnz as usize {
+            let (v, idx) = (BUFFER_A.get(2 * k), BUFFER_A.get(2 * k + 1));
+            if idx >= 0.0 && idx < CAPACITY as f64 {
+                d += v * BUFFER_B.get(idx as usize);
+            } else {
+                set_error(ERR_INDEX_RANGE);
+            }
+        }
+    }
+    d
+}
+
+// Sum of BUFFER_A[i] where the mask BUFFER_B[i] != 0.0. A NaN mask entry
+// counts as set.
+#[no_mangle]
+pub extern "C" fn masked_sum(len: u32) -> f64 {
+    let len = clamp_len(len);
+    let mut s = 0.0;
+    unsafe {
+        for i in 0..len {
+            if BUFFER_B.get(i) != 0.0 {
+                s += BUFFER_A.get(i);
+            }
+        }
+    }
+    s
+}
+
+// Number of mask entries masked_sum includes; masked_sum / masked_count is
+// the masked mean.
+#[no_mangle]
+pub extern "C" fn masked_count(len: u32) -> u32 {
+    let len = clamp_len(len);
+    let mut n = 0;
+    unsafe {
+        for i in 0..len {
+            n += (BUFFER_B.get(i) != 0.0) as u32;
+        }
+    }
+    n
+}
+
+// RESULT[i] = A[i] if A[i] > threshold, else B[i]. The conditional is a
+// plain value select (no early exit), which LLVM lowers to a branch-free
+// select/bitselect, so the loop still vectorizes. A NaN in A takes B.
+#[no_mangle]
+pub extern "C" fn select_gt(threshold: f64, len: u32) {
+    let len = clamp_len(len);
+    unsafe {
+        for i in 0..len {
+            let a = BUFFER_A.get(i);
+            RESULT.set(i, if a > threshold { a } else { BUFFER_B.get(i) });
+        }
+    }
+}
+
+// RESULT[i] = A[i] if A[i] < threshold, else B[i]. A NaN in A takes B.
+#[no_mangle]
+pub extern "C" fn select_lt(threshold: f64, len: u32) {
+    let len = clamp_len(len);
+    unsafe {
+        for i in 0..len {
+            let a = BUFFER_A.get(i);
+            RESULT.set(i, if a < threshold { a } else { BUFFER_B.get(i) });
+        }
+    }
+}
+
+// Comparison masks: RESULT[i] = 1.0 where BUFFER_A[i] satisfies the
+// predicate, else 0.0, as (cond as u32) as f64 so the loops stay
+// branch-free. NaN never satisfies any of them.
+#[no_mangle]
+pub extern "C" fn gt_mask(threshold: f64, len: u32) {
+    let len = clamp_len(len);
+    unsafe {
+        for i in 0..len {
+            RESULT.set(i, (BUFFER_A.get(i) > threshold) as u32 as f64);
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn lt_mask(threshold: f64, len: u32) {
+    let len = clamp_len(len);
+    unsafe {
+        for i in 0..len {
+            RESULT.set(i, (BUFFER_A.get(i) < threshold) as u32 as f64);
+        }
+    }
+}
+
+// 1.0 where |A[i] - value| <= tol. tol == 0.0 is exact equality, and an
+// infinity matches a value of the same sign.
+#[no_mangle]
+pub extern "C" fn eq_mask(value: f64, tol: f64, len: u32) {
+    let len = clamp_len(len);
+    unsafe {
+        for i in 0..len {
+            let x = BUFFER_A.get(i);
+            RESULT.set(i, (x == value || (x - value).abs() <= tol) as u32 as f64);
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn sum_f32(len: u32) -> f32 {
+    let len = clamp_len(len);
+    let mut s = 0.0;
+    unsafe {
+        for i in 0..len {
+            s += BUFFER_A_F32.get(i);
+        }
+    }
+    s
+}
+
+#[no_mangle]
+pub extern "C" fn dot_f32(len: u32) -> f32 {
+    let len = clamp_len(len);
+    let mut d = 0.0;
+    unsafe {
+        for i in 0..len {
+            d += BUFFER_A_F32.get(i) * BUFFER_B_F32.get(i);
+        }
+    }
+    d
+}
+
+// Mixed-precision dot: f32 weights in BUFFER_A_F32 against f64 activations
+// in BUFFER_B. Each weight is widened to f64 as it is read (exact, since
+// every f32 is an f64), and the products are accumulated in f64, so the
+// weights never need a widened copy.
+#[no_mangle]
+pub extern "C" fn dot_mixed(len: u32) -> f64 {
+    let len = clamp_len(len);
+    let mut d = 0.0;
+    unsafe {
+        for i in 0..len {
+            d += BUFFER_A_F32.get(i) as f64 * BUFFER_B.get(i);
+        }
+    }
+    d
+}
+
+#[no_mangle]
+pub extern "C" fn mul_f32(len: u32) {
+    let len = clamp_len(len);
+    unsafe {
+        for i in 0..len {
+            RESULT_F32.set(i, BUFFER_A_F32.get(i) * BUFFER_B_F32.get(i));
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn scale_f32(scalar: f32, len: u32) {
+    let len = clamp_len(len);
+    unsafe {
+        for i in 0..len {
+            BUFFER_A_F32.set(i, BUFFER_A_F32.get(i) * scalar);
+        }
+    }
+}
+
+// Sum of BUFFER_I32, widened to i64 so it cannot overflow: CAPACITY i32s
+// sum to well under 2^63.
+#[no_mangle]
+pub extern "C" fn sum_i32(len: u32) -> i64 {
+    let len = clamp_len(len);
+    let mut s = 0i64;
+    unsafe {
+        for i in 0..len {
+            s += BUFFER_I32.get(i) as i64;
+        }
+    }
+    s
+}
+
+// Sum of BUFFER_I32 saturated to the i32 range: i32::MAX or i32::MIN when
+// the true sum doesn't fit. The exact sum is accumulated in i64 and clamped
+// once at the end, so the result doesn't depend on element order (a running
+// i32::saturating_add can stick at a bound and then drift back off it).
+#[no_mangle]
+pub extern "C" fn sum_i32_saturating(len: u32) -> i32 {
+    sum_i32(len).clamp(i32::MIN as i64, i32::MAX as i64) as i32
+}
+
+// Exact widened sum of BUFFER_I32, like sum_i32, but sets ERR_OVERFLOW if
+// the total falls outside the i32 range (i.e. an i32 accumulator would have
+// returned a wrapped value).
+#[no_mangle]
+pub extern "C" fn sum_i32_checked(len: u32) -> i64 {
+    let s = sum_i32(len);
+    if i32::try_from(s).is_err() {
+        set_error(ERR_OVERFLOW);
+    }
+    s
+}
+
+// Smallest element of BUFFER_I32; i32::MAX for len == 0. Comparisons only,
+// so there is nothing to wrap or saturate.
+#[no_mangle]
+pub extern "C" fn min_i32(len: u32) -> i32 {
+    let len = clamp_len(len);
+    let mut m = i32::MAX;
+    unsafe {
+        for i in 0..len {
+            m = m.min(BUFFER_I32.get(i));
+        }
+    }
+    m
+}
+
+// Largest element of BUFFER_I32; i32::MIN for len == 0.
+#[no_mangle]
+pub extern "C" fn max_i32(len: u32) -> i32 {
+    let len = clamp_len(len);
+    let mut m = i32::MIN;
+    unsafe {
+        for i in 0..len {
+            m = m.max(BUFFER_I32.get(i));
+        }
+    }
+    m
+}
+
+// Element-wise bitwise ops: RESULT_I32[i] = BUFFER_I32[i] op BUFFER_B_I32[i]
+#[no_mangle]
+pub extern "C" fn and_i32(len: u32) {
+    let len = clamp_len(len);
+    unsafe {
+        for i in 0..len {
+            RESULT_I32.set(i, BUFFER_I32.get(i) & BUFFER_B_I32.get(i));
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn or_i32(len: u32) {
+    let len = clamp_len(len);
+    unsafe {
+        for i in 0..len {
+            RESULT_I32.set(i, BUFFER_I32.get(i) | BUFFER_B_I32.get(i));
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn xor_i32(len: u32) {
+    let len = clamp_len(len);
+    unsafe {
+        for i in 0..len {
+            RESULT_I32.set(i, BUFFER_I32.get(i) ^ BUFFER_B_I32.get(i));
+        }
+    }
+}
+
+// Total set bits across BUFFER_I32[0..len]. At most 32 * CAPACITY, which
+// fits in u32 for every supported capacity.
+#[no_mangle]
+pub extern "C" fn popcount_i32(len: u32) -> u32 {
+    let len = clamp_len(len);
+    let mut n = 0;
+    unsafe {
+        for i in 0..len {
+            n += BUFFER_I32.get(i).count_ones();
+        }
+    }
+    n
+}
+
+#[no_mangle]
+pub extern "C" fn get_buffer_a_offset() -> u32 {
+    addr_of!(BUFFER_A) as u32
+}
+
+// Bytes the host may read or write starting at get_buffer_a_offset():
+// [offset, offset + CAPACITY * 8) is exactly BUFFER_A, native little-endian
+// f64s. The same length applies to BUFFER_B and RESULT.
+#[no_mangle]
+pub extern "C" fn get_buffer_a_byte_len() -> u32 {
+    (CAPACITY * 8) as u32
+}
+
+#[no_mangle]
+pub extern "C" fn get_buffer_b_offset() -> u32 {
+    addr_of!(BUFFER_B) as u32
+}
+
+#[no_mangle]
+pub extern "C" fn get_result_offset() -> u32 {
+    addr_of!(RESULT) as u32
+}
+
+#[no_mangle]
+pub extern "C" fn get_buffer_a_f32_offset() -> u32 {
+    addr_of!(BUFFER_A_F32) as u32
+}
+
+#[no_mangle]
+pub extern "C" fn get_buffer_b_f32_offset() -> u32 {
+    addr_of!(BUFFER_B_F32) as u32
+}
+
+#[no_mangle]
+pub extern "C" fn get_result_f32_offset() -> u32 {
+    addr_of!(RESULT_F32) as u32
+}
+
+#[no_mangle]
+pub extern "C" fn get_buffer_i32_offset() -> u32 {
+    addr_of!(BUFFER_I32) as u32
+}
+
+#[no_mangle]
+pub extern "C" fn get_buffer_b_i32_offset() -> u32 {
+    addr_of!(BUFFER_B_I32) as u32
+}
+
+#[no_mangle]
+pub extern "C" fn get_result_i32_offset() -> u32 {
+    addr_of!(RESULT_I32) as u32
+}
+
+#[no_mangle]
+pub extern "C" fn get_buffer_i8_offset() -> u32 {
+    addr_of!(BUFFER_I8) as u32
+}
+
+#[no_mangle]
+pub extern "C" fn get_index_buffer_offset() -> u32 {
+    addr_of!(INDEX) as u32
+}
+
+// describe_layout writes this many little-endian u32s (52 bytes) at
+// out_offset, in this order:
+//   0 BUFFER_A offset        4 BUFFER_B offset       8 RESULT offset
+//  12 capacity (elements)   16 f64 element size (8)
+//  20 BUFFER_A_F32 offset   24 BUFFER_B_F32 offset  28 RESULT_F32 offset
+//  32 BUFFER_I32 offset     36 BUFFER_B_I32 offset  40 RESULT_I32 offset
+//  44 BUFFER_I8 offset      48 INDEX offset
+// New fields are only ever appended.
+const LAYOUT_WORDS: usize = 13;
+
+// Write the layout record above at out_offset (any alignment) in one call,
+// instead of a getter per buffer. The host must point it at 52 bytes it owns,
+// e.g. inside RESULT. Offset 0, or a record that would run past the end of
+// linear memory, writes nothing and sets ERR_INDEX_RANGE.
+#[no_mangle]
+pub extern "C" fn describe_layout(out_offset: u32) {
+    let out = out_offset as usize;
+    if out == 0 || out.saturating_add(LAYOUT_WORDS * 4) > memory_pages() * PAGE_SIZE {
+        set_error(ERR_INDEX_RANGE);
+        return;
+    }
+    let record: [u32; LAYOUT_WORDS] = [
+        get_buffer_a_offset(),
+        get_buffer_b_offset(),
+        get_result_offset(),
+        CAPACITY as u32,
+        size_of::<f64>() as u32,
+        get_buffer_a_f32_offset(),
+        get_buffer_b_f32_offset(),
+        get_result_f32_offset(),
+        get_buffer_i32_offset(),
+        get_buffer_b_i32_offset(),
+        get_result_i32_offset(),
+        get_buffer_i8_offset(),
+        get_index_buffer_offset(),
+    ];
+    let p = out as *mut u32;
+    for (i, w) in record.iter().enumerate() {
+        unsafe { p.add(i).write_unaligned(w.to_le()) }
+    }
+}
+
+// Bytes per element of the f64 buffers (BUFFER_A, BUFFER_B, RESULT)
+#[no_mangle]
+pub extern "C" fn element_size() -> u32 {
+    size_of::<f64>() as u32
+}
+
+// Guaranteed alignment, in bytes, of every get_*_offset() buffer start
+#[no_mangle]
+pub extern "C" fn buffer_alignment() -> u32 {
+    BUFFER_ALIGN as u32
+}
+
+#[no_mangle]
+pub extern "C" fn get_capacity() -> u32 {
+    CAPACITY as u32
+}
+
+// Every op clamps len to CAPACITY (and flags ERR_LEN_CLAMPED). Returns 1 if
+// len would be clamped (so the op would see fewer elements than asked), else
+// 0. Cheap enough for the host to call before dispatching.
+#[no_mangle]
+pub extern "C" fn check_len(len: u32) -> u32 {
+    (len as usize > CAPACITY) as u32
+}
+
+// Most recent ERR_* code set by any op since the last clear_error, or 0.
+#[no_mangle]
+pub extern "C" fn last_error() -> u32 {
+    LAST_ERROR.get()
+}
+
+#[no_mangle]
+pub extern "C" fn clear_error() {
+    LAST_ERROR.set(ERR_NONE);
+}
+
+// Return the module to its post-instantiation state for the next job,
+// without re-instantiating. Touches exactly:
+// - every static buffer, zeroed in full: BUFFER_A, BUFFER_B, RESULT, the
+//   f32 and i32 triples, BUFFER_I8 and INDEX;
+// - the contents of the reserve()d region, zeroed (the region itself stays
+//   reserved, since linear memory can't shrink, and memory_bytes_peak keeps
+//   counting it);
+// - the error register, cleared to ERR_NONE;
+// - the PRNG, reseeded with the default seed it starts with.
+// Nothing else in the module holds state.
+#[no_mangle]
+pub extern "C" fn reset() {
+    clear_all();
+    unsafe {
+        BUFFER_A_F32.fill(CAPACITY, 0.0);
+        BUFFER_B_F32.fill(CAPACITY, 0.0);
+        RESULT_F32.fill(CAPACITY, 0.0);
+        BUFFER_I32.fill(CAPACITY, 0);
+        BUFFER_B_I32.fill(CAPACITY, 0);
+        RESULT_I32.fill(CAPACITY, 0);
+        BUFFER_I8.fill(CAPACITY, 0);
+        INDEX.fill(CAPACITY, 0);
+        if DYN_OFFSET.get() != 0 {
+            core::ptr::write_bytes(DYN_OFFSET.get() as *mut f64, 0, DYN_LEN.get());
+        }
+    }
+    LAST_ERROR.set(ERR_NONE);
+    RNG_STATE.set(DEFAULT_SEED);
+}
+
+// Bumped whenever an existing export is removed or changes signature or
+// meaning. Adding exports doesn't bump it; hosts should refuse to run
+// against a version they don't know.
+const ABI_VERSION: u32 = 1;
+
+#[no_mangle]
+pub extern "C" fn abi_version() -> u32 {
+    ABI_VERSION
+}
+
+// FNV-1a of the crate version string, evaluated at compile time. Tells builds
+// from different releases apart; it says nothing about ABI compatibility.
+const fn fnv1a(bytes: &[u8]) -> u32 {
+    let mut h: u32 = 0x811C_9DC5;
+    let mut i = 0;
+    while i < bytes.len() {
+        h ^= bytes[i] as u32;
+        h = h.wrapping_mul(0x0100_0193);
+        i += 1;
+    }
+    h
+}
+
+const CRATE_VERSION_HASH: u32 = fnv1a(env!("CARGO_PKG_VERSION").as_bytes());
+
+#[no_mangle]
+pub extern "C" fn crate_version_hash() -> u32 {
+    CRATE_VERSION_HASH
+}
+
+// Capability IDs for has_feature, also the bit positions in feature_bits.
+// IDs are never reused. Everything but SIMD128 is always compiled in today;
+// the IDs exist so a host can check before calling once that changes.
+const FEATURE_SIMD128: u32 = 0; // sum_simd/dot_simd use wasm simd128 (else scalar fallback)
+const FEATURE_F32: u32 = 1; // *_f32 ops and buffers
+const FEATURE_I32: u32 = 2; // *_i32 ops and buffers
+const FEATURE_I8: u32 = 3; // quantize_i8 / dequantize_i8
+const FEATURE_FFT: u32 = 4; // fft / ifft
+const FEATURE_DYNAMIC: u32 = 5; // reserve and the *_at ops
+
+#[no_mangle]
+pub extern "C" fn feature_bits() -> u32 {
+    let mut bits = 1 << FEATURE_F32
+        | 1 << FEATURE_I32
+        | 1 << FEATURE_I8
+        | 1 << FEATURE_FFT
+        | 1 << FEATURE_DYNAMIC;
+    if cfg!(target_feature = "simd128") {
+        bits |= 1 << FEATURE_SIMD128;
+    }
+    bits
+}
+
+// 1 if capability feature_id is compiled in, else 0 (including unknown IDs).
+#[no_mangle]
+pub extern "C" fn has_feature(feature_id: u32) -> u32 {
+    feature_bits().checked_shr(feature_id).unwrap_or(0) & 1
 }
 
-// Panic handler for no_std
+// Panic handler for no_std. On wasm32 this executes `unreachable`, which
+// traps out to the host as a catchable runtime error instead of hanging the
+// calling thread. Other targets (host-side checks only) keep the spin.
 #[panic_handler]
 fn panic(_info: &core::panic::PanicInfo) -> ! {
+    #[cfg(target_arch = "wasm32")]
+    core::arch::wasm32::unreachable();
+    #[cfg(not(target_arch = "wasm32"))]
     loop {}
 }